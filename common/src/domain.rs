@@ -0,0 +1,119 @@
+//! Chains intents can originate from or be settled on.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize)]
+pub enum EthereumNetwork {
+    Mainnet,
+    Sepolia,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize)]
+pub enum SolanaCluster {
+    Mainnet,
+    Testnet,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize)]
+pub enum MantisNetwork {
+    Mainnet,
+    Testnet,
+}
+
+/// A chain an intent's tokens live on.
+///
+/// Parsed from and displayed as the kebab-case names used by the CLI, e.g. `ethereum-mainnet`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize)]
+pub enum Domain {
+    Ethereum(EthereumNetwork),
+    Solana(SolanaCluster),
+    /// The Mantis rollup, an SVM chain.
+    Mantis(MantisNetwork),
+}
+
+impl Domain {
+    pub const ALL: [Domain; 6] = [
+        Domain::Ethereum(EthereumNetwork::Mainnet),
+        Domain::Ethereum(EthereumNetwork::Sepolia),
+        Domain::Solana(SolanaCluster::Mainnet),
+        Domain::Solana(SolanaCluster::Testnet),
+        Domain::Mantis(MantisNetwork::Mainnet),
+        Domain::Mantis(MantisNetwork::Testnet),
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Domain::Ethereum(EthereumNetwork::Mainnet) => "ethereum-mainnet",
+            Domain::Ethereum(EthereumNetwork::Sepolia) => "ethereum-sepolia",
+            Domain::Solana(SolanaCluster::Mainnet) => "solana-mainnet",
+            Domain::Solana(SolanaCluster::Testnet) => "solana-testnet",
+            Domain::Mantis(MantisNetwork::Mainnet) => "mantis-mainnet",
+            Domain::Mantis(MantisNetwork::Testnet) => "mantis-testnet",
+        }
+    }
+
+    /// Whether the domain runs the Solana VM, i.e. uses Solana addresses and programs.
+    pub fn is_svm(&self) -> bool {
+        matches!(self, Domain::Solana(_) | Domain::Mantis(_))
+    }
+}
+
+impl fmt::Display for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Domain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Domain::ALL
+            .into_iter()
+            .find(|domain| domain.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown domain: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for domain in Domain::ALL {
+            assert_eq!(domain.to_string().parse::<Domain>(), Ok(domain));
+            assert_eq!(domain.name().to_uppercase().parse::<Domain>(), Ok(domain));
+        }
+        assert!("ethereum-goerli".parse::<Domain>().is_err());
+        assert!("".parse::<Domain>().is_err());
+    }
+
+    #[test]
+    fn names_are_distinct() {
+        for (i, a) in Domain::ALL.into_iter().enumerate() {
+            for b in &Domain::ALL[i + 1..] {
+                assert_ne!(a.name(), b.name());
+            }
+        }
+    }
+
+    #[test]
+    fn borsh_round_trips() {
+        for domain in Domain::ALL {
+            let bytes = borsh::to_vec(&domain).unwrap();
+            // stored in intent accounts, whose size is fixed at creation
+            assert_eq!(bytes.len(), 2);
+            assert_eq!(Domain::try_from_slice(&bytes).unwrap(), domain);
+        }
+    }
+
+    #[test]
+    fn only_solana_and_mantis_are_svm() {
+        for domain in Domain::ALL {
+            assert_eq!(domain.is_svm(), !matches!(domain, Domain::Ethereum(_)), "{}", domain);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Deterministic intent ids.
+//!
+//! An intent id is the hex encoded prefix of a SHA-256 hash over everything that
+//! identifies an intent, so anyone holding the [`UserIntent`] can recompute it off-chain.
+
+use crate::{Domain, EthereumNetwork, MantisNetwork, SolanaCluster, UserIntent};
+use sha2::{Digest, Sha256};
+
+/// Length of an intent id string, chosen to fit in a single Solana PDA seed.
+pub const INTENT_ID_LEN: usize = 32;
+
+const DOMAIN_SEPARATOR: &[u8] = b"mantis-intent-v1";
+
+/// Derives the id of `intent` from its domains, users, nonce, tokens, amounts and timeout.
+pub fn derive_intent_id(intent: &UserIntent) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(DOMAIN_SEPARATOR);
+    hasher.update(domain_tag(intent.src_domain));
+    hasher.update(domain_tag(intent.dst_domain));
+    // amounts are hashed in base units, decimals aren't stored on-chain on every chain
+    let amount_in = intent.amount_in.raw().to_string();
+    let amount_out = intent.amount_out.raw().to_string();
+    // strings are length prefixed so that field boundaries can't be shifted
+    for field in [
+        &intent.src_user,
+        &intent.dst_user,
+        &intent.token_in,
+        &amount_in,
+        &intent.token_out,
+        &amount_out,
+    ] {
+        hasher.update((field.len() as u32).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.update(intent.nonce.to_le_bytes());
+    hasher.update(intent.timeout.to_le_bytes());
+
+    hasher.finalize()[..INTENT_ID_LEN / 2]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Fixed tags, so that reordering the domain enums can't change intent ids.
+fn domain_tag(domain: Domain) -> [u8; 2] {
+    match domain {
+        Domain::Ethereum(EthereumNetwork::Mainnet) => [0, 0],
+        Domain::Ethereum(EthereumNetwork::Sepolia) => [0, 1],
+        Domain::Solana(SolanaCluster::Mainnet) => [1, 0],
+        Domain::Solana(SolanaCluster::Testnet) => [1, 1],
+        Domain::Mantis(MantisNetwork::Mainnet) => [2, 0],
+        Domain::Mantis(MantisNetwork::Testnet) => [2, 1],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenAmount;
+    use ruint::aliases::U256;
+
+    fn intent() -> UserIntent {
+        UserIntent {
+            src_domain: Domain::Ethereum(EthereumNetwork::Mainnet),
+            dst_domain: Domain::Solana(SolanaCluster::Mainnet),
+            src_user: "0x1111111111111111111111111111111111111111".to_string(),
+            dst_user: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+            token_in: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string(),
+            amount_in: TokenAmount::new(U256::from(1_500_000), 6),
+            token_out: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            amount_out: TokenAmount::new(U256::from(1_490_000), 6),
+            timeout: 1_700_000_000,
+            nonce: 7,
+        }
+    }
+
+    #[test]
+    fn derives_known_id() {
+        // pinned so that changing the derivation, which orphans stored intents, fails loudly
+        assert_eq!(derive_intent_id(&intent()), "028276e988403cdde9e765c138458a9e");
+    }
+
+    #[test]
+    fn is_deterministic() {
+        assert_eq!(derive_intent_id(&intent()), derive_intent_id(&intent()));
+    }
+
+    #[test]
+    fn has_intent_id_len_hex_chars() {
+        let id = derive_intent_id(&intent());
+        assert_eq!(id.len(), INTENT_ID_LEN);
+        assert!(id.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)));
+    }
+
+    #[test]
+    fn changes_with_every_field() {
+        let changes: [fn(&mut UserIntent); 10] = [
+            |intent| intent.src_domain = Domain::Ethereum(EthereumNetwork::Sepolia),
+            |intent| intent.dst_domain = Domain::Solana(SolanaCluster::Testnet),
+            |intent| intent.src_user = "0x2222222222222222222222222222222222222222".to_string(),
+            |intent| intent.dst_user = "So11111111111111111111111111111111111111112".to_string(),
+            |intent| intent.token_in = "0xdAC17F958D2ee523a2206206994597C13D831ec7".to_string(),
+            |intent| intent.amount_in = TokenAmount::new(U256::from(1_500_001), 6),
+            |intent| intent.token_out = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string(),
+            |intent| intent.amount_out = TokenAmount::new(U256::from(1_490_001), 6),
+            |intent| intent.timeout += 1,
+            |intent| intent.nonce += 1,
+        ];
+        let id = derive_intent_id(&intent());
+        for change in changes {
+            let mut changed = intent();
+            change(&mut changed);
+            assert_ne!(derive_intent_id(&changed), id, "{:?}", changed);
+        }
+    }
+
+    #[test]
+    fn ignores_decimals() {
+        let mut changed = intent();
+        changed.amount_in = TokenAmount::new(changed.amount_in.raw(), 18);
+        assert_eq!(derive_intent_id(&changed), derive_intent_id(&intent()));
+    }
+
+    #[test]
+    fn field_boundaries_can_not_shift() {
+        let mut a = intent();
+        a.src_user = "ab".to_string();
+        a.dst_user = "c".to_string();
+        let mut b = intent();
+        b.src_user = "a".to_string();
+        b.dst_user = "bc".to_string();
+        assert_ne!(derive_intent_id(&a), derive_intent_id(&b));
+    }
+
+    #[test]
+    fn domain_tags_are_distinct() {
+        for (i, a) in Domain::ALL.into_iter().enumerate() {
+            for b in &Domain::ALL[i + 1..] {
+                assert_ne!(domain_tag(a), domain_tag(*b), "{} and {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn changes_with_every_domain() {
+        let ids: Vec<_> = Domain::ALL
+            .into_iter()
+            .map(|domain| {
+                let mut intent = intent();
+                intent.dst_domain = domain;
+                derive_intent_id(&intent)
+            })
+            .collect();
+        for (i, id) in ids.iter().enumerate() {
+            assert!(!ids[i + 1..].contains(id));
+        }
+    }
+}
@@ -1,25 +1,184 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use ruint::aliases::U256;
 
-#[derive(Debug)]
+pub mod domain;
+pub mod intent_id;
+pub mod token_amount;
+
+pub use domain::{Domain, EthereumNetwork, MantisNetwork, SolanaCluster};
+pub use token_amount::{TokenAmount, TokenAmountError};
+
+/// An intent as stored by the escrow on its source domain.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Intent {
     pub intent_id: String,
-    pub user_in: String,
-    pub user_out: String,
+    pub src_domain: Domain,
+    /// Domain the user is paid out on, `None` if the escrow doesn't record it.
+    pub dst_domain: Option<Domain>,
+    pub src_user: String,
+    /// Receiver of `token_out` on the destination domain.
+    pub dst_user: String,
     pub token_in: String,
-    pub amount_in: U256,
+    pub amount_in: TokenAmount,
+    /// Address, mint or cross-chain denom of the output token on the destination domain.
     pub token_out: String,
-    pub amount_out: U256,
-    pub winner_solver: String,
+    pub amount_out: TokenAmount,
+    /// Solver that won the auction, once it has run.
+    pub winner_solver: Option<String>,
+    /// Deadline as a unix timestamp (seconds), after which the user can cancel the intent.
     pub timeout: u64,
-    pub single_domain: bool,
 }
 
-#[derive(Debug, BorshSerialize, BorshDeserialize)]
+impl Intent {
+    pub fn is_single_domain(&self) -> bool {
+        self.dst_domain == Some(self.src_domain)
+    }
+
+    /// Returns the status of the stored intent at unix timestamp `now`.
+    pub fn status(&self, now: u64) -> IntentStatus {
+        if self.timeout < now {
+            return IntentStatus::Expired;
+        }
+        match &self.winner_solver {
+            Some(winner_solver) => IntentStatus::Auctioned {
+                winner_solver: winner_solver.clone(),
+            },
+            None => IntentStatus::Escrowed,
+        }
+    }
+}
+
+/// Lifecycle of an intent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IntentStatus {
+    /// The input tokens are escrowed, waiting for the auction.
+    Escrowed,
+    /// The auction picked a solver, which can now settle the intent.
+    Auctioned { winner_solver: String },
+    /// The solver paid the user and received the escrowed tokens.
+    Settled,
+    /// The user cancelled the intent and got the escrowed tokens back.
+    Cancelled,
+    /// The deadline passed without settlement, the user can cancel the intent.
+    Expired,
+}
+
+impl IntentStatus {
+    /// Whether the intent can't change anymore.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, IntentStatus::Settled | IntentStatus::Cancelled)
+    }
+}
+
+/// An intent as submitted by a user, before it's escrowed.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub struct UserIntent {
+    /// Domain the input tokens are escrowed on.
+    pub src_domain: Domain,
+    /// Domain the user is paid out on.
+    pub dst_domain: Domain,
+    /// Owner of the input tokens, who submits the intent.
+    pub src_user: String,
+    /// Receiver of `token_out` on the destination domain.
+    pub dst_user: String,
     pub token_in: String,
-    pub amount_in: String,
+    pub amount_in: TokenAmount,
+    /// Address, mint or cross-chain denom of the output token on the destination domain.
     pub token_out: String,
-    pub amount_out: String,
-    pub user_address: String,
+    pub amount_out: TokenAmount,
+    /// Deadline as a unix timestamp (seconds), after which the user can cancel the intent
+    /// and reclaim the escrowed tokens.
+    pub timeout: u64,
+    /// Distinguishes otherwise identical intents of the same user.
+    pub nonce: u64,
+}
+
+impl UserIntent {
+    /// Returns the deterministic id of this intent, see [`intent_id::derive_intent_id`].
+    pub fn intent_id(&self) -> String {
+        intent_id::derive_intent_id(self)
+    }
+
+    pub fn is_single_domain(&self) -> bool {
+        self.src_domain == self.dst_domain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruint::aliases::U256;
+
+    fn intent(winner_solver: Option<&str>, timeout: u64) -> Intent {
+        Intent {
+            intent_id: "00112233445566778899aabbccddeeff".to_string(),
+            src_domain: Domain::Solana(SolanaCluster::Mainnet),
+            dst_domain: Some(Domain::Ethereum(EthereumNetwork::Mainnet)),
+            src_user: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+            dst_user: "0x1111111111111111111111111111111111111111".to_string(),
+            token_in: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            amount_in: TokenAmount::new(U256::from(1_500_000), 6),
+            token_out: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+            amount_out: TokenAmount::new(U256::from(1_490_000), 6),
+            winner_solver: winner_solver.map(str::to_string),
+            timeout,
+        }
+    }
+
+    #[test]
+    fn status_follows_auction_and_deadline() {
+        assert_eq!(intent(None, 100).status(50), IntentStatus::Escrowed);
+        assert_eq!(
+            intent(Some("solver"), 100).status(50),
+            IntentStatus::Auctioned {
+                winner_solver: "solver".to_string()
+            }
+        );
+        // the escrows only allow cancelling once the deadline has passed
+        assert_eq!(intent(None, 100).status(100), IntentStatus::Escrowed);
+        assert_eq!(intent(None, 100).status(101), IntentStatus::Expired);
+        assert_eq!(intent(Some("solver"), 100).status(101), IntentStatus::Expired);
+    }
+
+    #[test]
+    fn only_settled_and_cancelled_are_terminal() {
+        assert!(IntentStatus::Settled.is_terminal());
+        assert!(IntentStatus::Cancelled.is_terminal());
+        assert!(!IntentStatus::Escrowed.is_terminal());
+        assert!(!IntentStatus::Expired.is_terminal());
+        assert!(!IntentStatus::Auctioned {
+            winner_solver: "solver".to_string()
+        }
+        .is_terminal());
+    }
+
+    #[test]
+    fn single_domain_needs_a_known_destination() {
+        let mut intent = intent(None, 100);
+        assert!(!intent.is_single_domain());
+        intent.dst_domain = Some(intent.src_domain);
+        assert!(intent.is_single_domain());
+        intent.dst_domain = None;
+        assert!(!intent.is_single_domain());
+    }
+
+    #[test]
+    fn user_intent_borsh_round_trips() {
+        let user_intent = UserIntent {
+            src_domain: Domain::Mantis(MantisNetwork::Testnet),
+            dst_domain: Domain::Ethereum(EthereumNetwork::Sepolia),
+            src_user: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+            dst_user: "0x1111111111111111111111111111111111111111".to_string(),
+            token_in: "So11111111111111111111111111111111111111112".to_string(),
+            amount_in: TokenAmount::new(U256::from(10), 9),
+            token_out: "0xfff9976782d46cc05630d1f6ebab18b2324d6b14".to_string(),
+            amount_out: TokenAmount::new(U256::MAX, 18),
+            timeout: 1_700_000_000,
+            nonce: 42,
+        };
+        let bytes = borsh::to_vec(&user_intent).unwrap();
+        let decoded = UserIntent::try_from_slice(&bytes).unwrap();
+        assert_eq!(decoded, user_intent);
+        assert_eq!(decoded.intent_id(), user_intent.intent_id());
+        assert!(!decoded.is_single_domain());
+    }
 }
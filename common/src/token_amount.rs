@@ -0,0 +1,302 @@
+//! Token amounts with their decimals.
+//!
+//! Amounts are kept in base units (wei, lamports, ...) next to the number of
+//! decimals of the token, so they can be parsed from and displayed as human
+//! readable strings like `"1.5"` without floating point.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use ruint::aliases::U256;
+use std::fmt;
+use std::io::{Read, Result as IoResult, Write};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TokenAmountError {
+    #[error("Invalid amount: {0:?}")]
+    InvalidAmount(String),
+    #[error("Amount {amount:?} has more than {decimals} decimals")]
+    TooManyDecimals { amount: String, decimals: u8 },
+    #[error("Amounts have different decimals: {0} and {1}")]
+    DecimalsMismatch(u8, u8),
+    #[error("Amount overflow")]
+    Overflow,
+}
+
+/// An amount of some token, in base units, together with the token's decimals.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TokenAmount {
+    raw: U256,
+    decimals: u8,
+}
+
+impl TokenAmount {
+    /// Creates an amount from `raw` base units.
+    pub const fn new(raw: U256, decimals: u8) -> Self {
+        Self { raw, decimals }
+    }
+
+    pub const fn zero(decimals: u8) -> Self {
+        Self::new(U256::ZERO, decimals)
+    }
+
+    /// Parses a human readable amount such as `"1.5"`, scaling it by `decimals`.
+    pub fn parse_units(amount: &str, decimals: u8) -> Result<Self, TokenAmountError> {
+        let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+            return Err(TokenAmountError::InvalidAmount(amount.to_string()));
+        }
+        // trailing zeros don't add precision, `"1.50"` fits a single decimal
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > decimals as usize {
+            return Err(TokenAmountError::TooManyDecimals {
+                amount: amount.to_string(),
+                decimals,
+            });
+        }
+
+        let padding = "0".repeat(decimals as usize - fraction.len());
+        let digits = format!("{}{}{}", whole, fraction, padding);
+        // only digits are left, so parsing can only fail on overflow
+        let raw = U256::from_str_radix(&digits, 10).map_err(|_| TokenAmountError::Overflow)?;
+        Ok(Self::new(raw, decimals))
+    }
+
+    /// Parses an integer amount of base units such as `"1500000"`.
+    pub fn parse_base_units(amount: &str, decimals: u8) -> Result<Self, TokenAmountError> {
+        if amount.is_empty() || !amount.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(TokenAmountError::InvalidAmount(amount.to_string()));
+        }
+        let raw = U256::from_str_radix(amount, 10).map_err(|_| TokenAmountError::Overflow)?;
+        Ok(Self::new(raw, decimals))
+    }
+
+    /// Returns the amount in base units.
+    pub const fn raw(&self) -> U256 {
+        self.raw
+    }
+
+    pub const fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.raw.is_zero()
+    }
+
+    pub fn checked_add(self, other: Self) -> Result<Self, TokenAmountError> {
+        self.check_decimals(&other)?;
+        let raw = self.raw.checked_add(other.raw).ok_or(TokenAmountError::Overflow)?;
+        Ok(Self::new(raw, self.decimals))
+    }
+
+    pub fn checked_sub(self, other: Self) -> Result<Self, TokenAmountError> {
+        self.check_decimals(&other)?;
+        let raw = self.raw.checked_sub(other.raw).ok_or(TokenAmountError::Overflow)?;
+        Ok(Self::new(raw, self.decimals))
+    }
+
+    /// Multiplies by `numerator / denominator`, rounding down, e.g. to apply a price or fee rate.
+    pub fn checked_mul_div(self, numerator: U256, denominator: U256) -> Result<Self, TokenAmountError> {
+        let raw = self
+            .raw
+            .checked_mul(numerator)
+            .and_then(|product| product.checked_div(denominator))
+            .ok_or(TokenAmountError::Overflow)?;
+        Ok(Self::new(raw, self.decimals))
+    }
+
+    /// Converts the base units into a `u64`, as used by SPL token amounts.
+    pub fn to_u64(&self) -> Result<u64, TokenAmountError> {
+        u64::try_from(self.raw).map_err(|_| TokenAmountError::Overflow)
+    }
+
+    fn check_decimals(&self, other: &Self) -> Result<(), TokenAmountError> {
+        if self.decimals != other.decimals {
+            return Err(TokenAmountError::DecimalsMismatch(self.decimals, other.decimals));
+        }
+        Ok(())
+    }
+}
+
+/// Formats the amount in human readable units, without trailing zeros.
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimals = self.decimals as usize;
+        if decimals == 0 {
+            return write!(f, "{}", self.raw);
+        }
+        let digits = format!("{:0>width$}", self.raw.to_string(), width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            write!(f, "{}", whole)
+        } else {
+            write!(f, "{}.{}", whole, fraction)
+        }
+    }
+}
+
+impl BorshSerialize for TokenAmount {
+    fn serialize<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        BorshSerialize::serialize(&self.raw.to_le_bytes::<32>(), writer)?;
+        BorshSerialize::serialize(&self.decimals, writer)
+    }
+}
+
+impl BorshDeserialize for TokenAmount {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> IoResult<Self> {
+        let raw = <[u8; 32]>::deserialize_reader(reader)?;
+        let decimals = u8::deserialize_reader(reader)?;
+        Ok(Self::new(U256::from_le_bytes(raw), decimals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(raw: u64, decimals: u8) -> TokenAmount {
+        TokenAmount::new(U256::from(raw), decimals)
+    }
+
+    #[test]
+    fn parse_units_scales_by_decimals() {
+        assert_eq!(TokenAmount::parse_units("1.5", 6), Ok(amount(1_500_000, 6)));
+        assert_eq!(TokenAmount::parse_units("42", 0), Ok(amount(42, 0)));
+        assert_eq!(TokenAmount::parse_units("0.000001", 6), Ok(amount(1, 6)));
+        assert_eq!(TokenAmount::parse_units("1.", 2), Ok(amount(100, 2)));
+        assert_eq!(TokenAmount::parse_units(".5", 2), Ok(amount(50, 2)));
+    }
+
+    #[test]
+    fn parse_units_accepts_leading_and_trailing_zeros() {
+        assert_eq!(TokenAmount::parse_units("001.500", 6), Ok(amount(1_500_000, 6)));
+        assert_eq!(TokenAmount::parse_units("1.5000000000", 6), Ok(amount(1_500_000, 6)));
+        assert_eq!(TokenAmount::parse_units("10.0", 0), Ok(amount(10, 0)));
+        assert_eq!(TokenAmount::parse_units("0.0", 6), Ok(amount(0, 6)));
+    }
+
+    #[test]
+    fn parse_units_rejects_too_many_decimals() {
+        assert_eq!(
+            TokenAmount::parse_units("1.0000001", 6),
+            Err(TokenAmountError::TooManyDecimals {
+                amount: "1.0000001".to_string(),
+                decimals: 6,
+            })
+        );
+        assert!(matches!(
+            TokenAmount::parse_units("0.5", 0),
+            Err(TokenAmountError::TooManyDecimals { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_units_rejects_invalid_amounts() {
+        for invalid in ["", ".", "-1", "+1", "1.2.3", "1,5", " 1", "1e6", "0x10"] {
+            assert_eq!(
+                TokenAmount::parse_units(invalid, 6),
+                Err(TokenAmountError::InvalidAmount(invalid.to_string())),
+                "{:?}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn parse_units_rejects_overflow() {
+        let max = U256::MAX.to_string();
+        assert_eq!(TokenAmount::parse_units(&max, 0), Ok(TokenAmount::new(U256::MAX, 0)));
+        assert_eq!(TokenAmount::parse_units(&max, 1), Err(TokenAmountError::Overflow));
+        assert_eq!(TokenAmount::parse_units(&format!("{}0", max), 0), Err(TokenAmountError::Overflow));
+    }
+
+    #[test]
+    fn parse_base_units_keeps_raw_amount() {
+        assert_eq!(TokenAmount::parse_base_units("1500000", 6), Ok(amount(1_500_000, 6)));
+        assert!(matches!(
+            TokenAmount::parse_base_units("1.5", 6),
+            Err(TokenAmountError::InvalidAmount(_))
+        ));
+        assert!(matches!(
+            TokenAmount::parse_base_units("", 6),
+            Err(TokenAmountError::InvalidAmount(_))
+        ));
+    }
+
+    #[test]
+    fn displays_without_trailing_zeros() {
+        assert_eq!(amount(1_500_000, 6).to_string(), "1.5");
+        assert_eq!(amount(1_000_000, 6).to_string(), "1");
+        assert_eq!(amount(1, 6).to_string(), "0.000001");
+        assert_eq!(amount(0, 6).to_string(), "0");
+        assert_eq!(amount(42, 0).to_string(), "42");
+        assert_eq!(amount(120, 0).to_string(), "120");
+        assert_eq!(TokenAmount::new(U256::MAX, 0).to_string(), U256::MAX.to_string());
+    }
+
+    #[test]
+    fn display_round_trips_through_parse_units() {
+        for (raw, decimals) in [(1_500_000, 6), (1, 18), (0, 9), (123_456_789, 3), (10, 0)] {
+            let amount = amount(raw, decimals);
+            assert_eq!(TokenAmount::parse_units(&amount.to_string(), decimals), Ok(amount));
+        }
+    }
+
+    #[test]
+    fn to_u64_rejects_overflow() {
+        assert_eq!(amount(u64::MAX, 6).to_u64(), Ok(u64::MAX));
+        let too_large = TokenAmount::new(U256::from(u64::MAX) + U256::from(1), 6);
+        assert_eq!(too_large.to_u64(), Err(TokenAmountError::Overflow));
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        assert_eq!(amount(1, 6).checked_add(amount(2, 6)), Ok(amount(3, 6)));
+        assert_eq!(amount(3, 6).checked_sub(amount(2, 6)), Ok(amount(1, 6)));
+        assert_eq!(
+            amount(1, 6).checked_mul_div(U256::from(3), U256::from(2)),
+            Ok(amount(1, 6))
+        );
+        assert_eq!(
+            amount(1_000, 6).checked_mul_div(U256::from(997), U256::from(1_000)),
+            Ok(amount(997, 6))
+        );
+    }
+
+    #[test]
+    fn checked_arithmetic_rejects_mismatched_decimals() {
+        assert_eq!(
+            amount(1, 6).checked_add(amount(1, 18)),
+            Err(TokenAmountError::DecimalsMismatch(6, 18))
+        );
+        assert_eq!(
+            amount(1, 18).checked_sub(amount(1, 6)),
+            Err(TokenAmountError::DecimalsMismatch(18, 6))
+        );
+    }
+
+    #[test]
+    fn checked_arithmetic_rejects_overflow() {
+        let max = TokenAmount::new(U256::MAX, 6);
+        assert_eq!(max.checked_add(amount(1, 6)), Err(TokenAmountError::Overflow));
+        assert_eq!(amount(1, 6).checked_sub(amount(2, 6)), Err(TokenAmountError::Overflow));
+        assert_eq!(
+            max.checked_mul_div(U256::from(2), U256::from(2)),
+            Err(TokenAmountError::Overflow)
+        );
+        assert_eq!(
+            amount(1, 6).checked_mul_div(U256::from(1), U256::ZERO),
+            Err(TokenAmountError::Overflow)
+        );
+    }
+
+    #[test]
+    fn borsh_round_trips() {
+        let amount = TokenAmount::new(U256::MAX - U256::from(1), 18);
+        let bytes = borsh::to_vec(&amount).unwrap();
+        assert_eq!(bytes.len(), 33);
+        assert_eq!(TokenAmount::try_from_slice(&bytes).unwrap(), amount);
+    }
+}
@@ -24,7 +24,7 @@ interface IICS20TransferBank {
  * Contains data for transferring tokens from a solver to a user.
  */
 struct SolverTransfer {
-    uint intentId;             // Unique identifier for the intent
+    uint intentId;             // Unique identifier for the intent, 32 hex digit ids of other domains read as a hex number
     address tokenOut;          // Address of the token to be transferred
     uint256 amountOut;         // Amount of tokens to be transferred
     address dstUser;           // Destination user for the transfer
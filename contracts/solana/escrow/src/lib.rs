@@ -1,20 +1,527 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
 use mantis_common::UserIntent;
 
 declare_id!("8DFWLK3ADs4qm1s394g9H7Vfyngad7DBpNXiaERoAvED");
 
+pub const INTENT_SEED: &[u8] = b"intent";
+pub const AUCTIONEER_SEED: &[u8] = b"auctioneer";
+
 #[program]
 pub mod mantis_escrow_program {
     use super::*;
 
+    /// Creates the intent account for `intent_id` and moves `amount` of the input
+    /// token from the user into a vault owned by that account.
     pub fn escrow_and_store_intent(
-        _ctx: Context<Initialize>,
-        _amount: u64,
-        _new_intent: UserIntent,
+        ctx: Context<EscrowAndStoreIntent>,
+        intent_id: String,
+        amount: u64,
+        new_intent: UserIntent,
+    ) -> Result<()> {
+        require!(intent_id == new_intent.intent_id(), EscrowError::InvalidIntentId);
+        require!(amount > 0, EscrowError::InvalidAmount);
+        require!(
+            new_intent.src_user == ctx.accounts.user.key().to_string(),
+            EscrowError::SrcUserMismatch
+        );
+        require!(
+            new_intent.token_in == ctx.accounts.token_in_mint.key().to_string(),
+            EscrowError::TokenInMismatch
+        );
+        require!(
+            new_intent.amount_in.decimals() == ctx.accounts.token_in_mint.decimals,
+            EscrowError::DecimalsMismatch
+        );
+        let amount_in = new_intent
+            .amount_in
+            .to_u64()
+            .map_err(|_| EscrowError::InvalidAmount)?;
+        require!(amount_in == amount, EscrowError::AmountMismatch);
+        let amount_out = u128::try_from(new_intent.amount_out.raw())
+            .map_err(|_| EscrowError::InvalidAmount)?;
+        require!(
+            new_intent.token_out.len() <= IntentState::MAX_TOKEN_OUT_LEN,
+            EscrowError::TokenOutTooLong
+        );
+        require!(
+            new_intent.dst_user.len() <= IntentState::MAX_DST_USER_LEN,
+            EscrowError::DstUserTooLong
+        );
+        // nothing releases a cross-domain intent to its solver yet, and cancelling would
+        // refund it directly while the bridge may still pay it out
+        require!(
+            new_intent.is_single_domain(),
+            EscrowError::CrossDomainUnsupported
+        );
+        // single domain intents are settled to the user's own token account
+        require!(
+            new_intent.dst_user == new_intent.src_user,
+            EscrowError::DstUserMismatch
+        );
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    mint: ctx.accounts.token_in_mint.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.token_in_mint.decimals,
+        )?;
+
+        let intent = &mut ctx.accounts.intent;
+        intent.intent_id = intent_id;
+        intent.user = ctx.accounts.user.key();
+        intent.dst_domain = new_intent.dst_domain.name().to_string();
+        intent.dst_user = new_intent.dst_user;
+        intent.token_in = ctx.accounts.token_in_mint.key();
+        intent.amount_in = amount;
+        intent.token_out = new_intent.token_out;
+        intent.amount_out = amount_out;
+        intent.winner_solver = String::new();
+        intent.timeout = new_intent.timeout;
+        intent.bump = ctx.bumps.intent;
+
+        emit!(IntentEscrowed {
+            intent_id: intent.intent_id.clone(),
+            user: intent.user,
+            token_in: intent.token_in,
+            amount_in: intent.amount_in,
+            token_out: intent.token_out.clone(),
+            amount_out: intent.amount_out,
+            timeout: intent.timeout,
+        });
+        Ok(())
+    }
+
+    /// Creates the auctioneer account, making the signer the authority allowed to
+    /// publish auction results. Only callable by the program's upgrade authority.
+    pub fn initialize_auctioneer(ctx: Context<InitializeAuctioneer>) -> Result<()> {
+        let auctioneer = &mut ctx.accounts.auctioneer;
+        auctioneer.authority = ctx.accounts.authority.key();
+        auctioneer.bump = ctx.bumps.auctioneer;
+        Ok(())
+    }
+
+    /// Records the auction result for an intent. Only callable by the auctioneer authority,
+    /// once per intent and before it times out, so a winner can't be swapped out after paying.
+    pub fn update_auction_data(
+        ctx: Context<UpdateAuctionData>,
+        _intent_id: String,
+        amount_out: u128,
+        winner_solver: String,
     ) -> Result<()> {
-        unimplemented!()
+        require!(
+            winner_solver.len() <= IntentState::MAX_WINNER_SOLVER_LEN,
+            EscrowError::WinnerSolverTooLong
+        );
+
+        let intent = &mut ctx.accounts.intent;
+        require!(intent.winner_solver.is_empty(), EscrowError::AuctionAlreadyWon);
+        let now = Clock::get()?.unix_timestamp as u64;
+        require!(now <= intent.timeout, EscrowError::IntentExpired);
+        intent.amount_out = amount_out;
+        intent.winner_solver = winner_solver;
+
+        emit!(AuctionUpdated {
+            intent_id: intent.intent_id.clone(),
+            amount_out,
+            winner_solver: intent.winner_solver.clone(),
+        });
+        Ok(())
     }
+
+    /// Single domain settlement: the winning solver pays `amount_out` of the output
+    /// token to the user and receives the escrowed input tokens. Closes the intent.
+    pub fn send_funds_to_user(ctx: Context<SendFundsToUser>) -> Result<()> {
+        let intent = &ctx.accounts.intent;
+        require!(
+            !intent.winner_solver.is_empty()
+                && intent.winner_solver == ctx.accounts.solver.key().to_string(),
+            EscrowError::WinnerSolverMismatch
+        );
+        require!(
+            intent.token_out == ctx.accounts.token_out_mint.key().to_string(),
+            EscrowError::TokenOutMismatch
+        );
+        let amount_out = u64::try_from(intent.amount_out).map_err(|_| EscrowError::InvalidAmount)?;
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_out_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.solver_token_out_account.to_account_info(),
+                    mint: ctx.accounts.token_out_mint.to_account_info(),
+                    to: ctx.accounts.user_token_out_account.to_account_info(),
+                    authority: ctx.accounts.solver.to_account_info(),
+                },
+            ),
+            amount_out,
+            ctx.accounts.token_out_mint.decimals,
+        )?;
+
+        release_escrow(
+            &ctx.accounts.intent,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.token_in_mint,
+            ctx.accounts.solver_token_in_account.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            &ctx.accounts.token_in_program,
+        )?;
+
+        emit!(IntentSettled {
+            intent_id: ctx.accounts.intent.intent_id.clone(),
+            solver: ctx.accounts.solver.key(),
+            amount_out,
+        });
+        Ok(())
+    }
+
+    /// Refunds the escrowed tokens to the user once the intent timed out. Closes the intent.
+    pub fn user_cancel_intent(ctx: Context<UserCancelIntent>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp as u64;
+        require!(
+            ctx.accounts.intent.timeout < now,
+            EscrowError::IntentNotExpired
+        );
+
+        release_escrow(
+            &ctx.accounts.intent,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.token_in_mint,
+            ctx.accounts.user_token_account.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(IntentCancelled {
+            intent_id: ctx.accounts.intent.intent_id.clone(),
+            user: ctx.accounts.user.key(),
+        });
+        Ok(())
+    }
+}
+
+/// Moves everything held in the intent vault to `to` and closes the vault,
+/// returning its rent to `rent_receiver`.
+fn release_escrow<'info>(
+    intent: &Account<'info, IntentState>,
+    escrow_token_account: &InterfaceAccount<'info, TokenAccount>,
+    token_in_mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    rent_receiver: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    let bump = [intent.bump];
+    let seeds: &[&[u8]] = &[INTENT_SEED, intent.intent_id.as_bytes(), &bump];
+    let signer_seeds = &[seeds];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: escrow_token_account.to_account_info(),
+                mint: token_in_mint.to_account_info(),
+                to,
+                authority: intent.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        escrow_token_account.amount,
+        token_in_mint.decimals,
+    )?;
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: escrow_token_account.to_account_info(),
+            destination: rent_receiver,
+            authority: intent.to_account_info(),
+        },
+        signer_seeds,
+    ))
+}
+
+#[derive(Accounts)]
+#[instruction(intent_id: String)]
+pub struct EscrowAndStoreIntent<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + IntentState::INIT_SPACE,
+        seeds = [INTENT_SEED, intent_id.as_bytes()],
+        bump,
+    )]
+    pub intent: Account<'info, IntentState>,
+    #[account(mint::token_program = token_program)]
+    pub token_in_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = token_in_mint,
+        token::authority = user,
+        token::token_program = token_program,
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// Vault holding the escrowed input tokens, owned by the intent account.
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = token_in_mint,
+        associated_token::authority = intent,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct Initialize {}
+pub struct InitializeAuctioneer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Auctioneer::INIT_SPACE,
+        seeds = [AUCTIONEER_SEED],
+        bump,
+    )]
+    pub auctioneer: Account<'info, Auctioneer>,
+    /// Program data of this program, holding its upgrade authority.
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ EscrowError::NotUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(intent_id: String)]
+pub struct UpdateAuctionData<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [AUCTIONEER_SEED],
+        bump = auctioneer.bump,
+        has_one = authority @ EscrowError::NotAuctioneer,
+    )]
+    pub auctioneer: Account<'info, Auctioneer>,
+    #[account(
+        mut,
+        seeds = [INTENT_SEED, intent_id.as_bytes()],
+        bump = intent.bump,
+    )]
+    pub intent: Account<'info, IntentState>,
+}
+
+#[derive(Accounts)]
+pub struct SendFundsToUser<'info> {
+    pub solver: Signer<'info>,
+    /// CHECK: only receives the intent and vault rent, checked against the intent user.
+    #[account(mut, address = intent.user @ EscrowError::SrcUserMismatch)]
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [INTENT_SEED, intent.intent_id.as_bytes()],
+        bump = intent.bump,
+    )]
+    pub intent: Account<'info, IntentState>,
+    #[account(address = intent.token_in @ EscrowError::TokenInMismatch)]
+    pub token_in_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = token_in_mint,
+        associated_token::authority = intent,
+        associated_token::token_program = token_in_program,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = token_in_mint,
+        token::authority = solver,
+        token::token_program = token_in_program,
+    )]
+    pub solver_token_in_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_out_program)]
+    pub token_out_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = token_out_mint,
+        token::authority = solver,
+        token::token_program = token_out_program,
+    )]
+    pub solver_token_out_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = token_out_mint,
+        token::authority = user,
+        token::token_program = token_out_program,
+    )]
+    pub user_token_out_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_in_program: Interface<'info, TokenInterface>,
+    pub token_out_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UserCancelIntent<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        close = user,
+        has_one = user @ EscrowError::SrcUserMismatch,
+        seeds = [INTENT_SEED, intent.intent_id.as_bytes()],
+        bump = intent.bump,
+    )]
+    pub intent: Account<'info, IntentState>,
+    #[account(address = intent.token_in @ EscrowError::TokenInMismatch)]
+    pub token_in_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = token_in_mint,
+        associated_token::authority = intent,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = token_in_mint,
+        token::authority = user,
+        token::token_program = token_program,
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// On-chain state of an escrowed intent, stored at `[INTENT_SEED, intent_id]`.
+#[account]
+#[derive(InitSpace)]
+pub struct IntentState {
+    #[max_len(32)]
+    pub intent_id: String,
+    pub user: Pubkey,
+    /// Name of the domain the user is paid out on, e.g. `ethereum-mainnet`, see `Domain::name`.
+    #[max_len(16)]
+    pub dst_domain: String,
+    /// Receiver of the output token on the destination domain.
+    #[max_len(64)]
+    pub dst_user: String,
+    pub token_in: Pubkey,
+    pub amount_in: u64,
+    /// Output token, either a mint address or a cross-chain denom.
+    #[max_len(64)]
+    pub token_out: String,
+    /// Minimum output amount in base units; wider than `u64` to fit cross-chain tokens.
+    pub amount_out: u128,
+    /// Winning solver address, empty until the auction is settled.
+    #[max_len(64)]
+    pub winner_solver: String,
+    /// Unix timestamp (seconds) after which the user can cancel the intent.
+    pub timeout: u64,
+    pub bump: u8,
+}
+
+impl IntentState {
+    pub const MAX_DST_USER_LEN: usize = 64;
+    pub const MAX_TOKEN_OUT_LEN: usize = 64;
+    pub const MAX_WINNER_SOLVER_LEN: usize = 64;
+}
+
+/// Singleton holding the authority that publishes auction results, stored at `[AUCTIONEER_SEED]`.
+#[account]
+#[derive(InitSpace)]
+pub struct Auctioneer {
+    pub authority: Pubkey,
+    pub bump: u8,
+}
+
+/// Emitted when a user escrows the input tokens of a new intent.
+#[event]
+pub struct IntentEscrowed {
+    pub intent_id: String,
+    pub user: Pubkey,
+    pub token_in: Pubkey,
+    pub amount_in: u64,
+    pub token_out: String,
+    pub amount_out: u128,
+    pub timeout: u64,
+}
+
+/// Emitted when the auctioneer records the auction result of an intent.
+#[event]
+pub struct AuctionUpdated {
+    pub intent_id: String,
+    pub amount_out: u128,
+    pub winner_solver: String,
+}
+
+/// Emitted when the winning solver settles a single domain intent.
+#[event]
+pub struct IntentSettled {
+    pub intent_id: String,
+    pub solver: Pubkey,
+    pub amount_out: u64,
+}
+
+/// Emitted when the user cancels an expired intent and gets the escrowed tokens back.
+#[event]
+pub struct IntentCancelled {
+    pub intent_id: String,
+    pub user: Pubkey,
+}
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("Intent id doesn't match the intent")]
+    InvalidIntentId,
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Escrowed amount doesn't match intent amount_in")]
+    AmountMismatch,
+    #[msg("Intent amount_in decimals != token_in_mint decimals")]
+    DecimalsMismatch,
+    #[msg("Intent src_user != signer")]
+    SrcUserMismatch,
+    #[msg("Intent token_in != token_in_mint")]
+    TokenInMismatch,
+    #[msg("Intent token_out is too long")]
+    TokenOutTooLong,
+    #[msg("Intent token_out != token_out_mint")]
+    TokenOutMismatch,
+    #[msg("Intent winner_solver != solver")]
+    WinnerSolverMismatch,
+    #[msg("Winner solver is too long")]
+    WinnerSolverTooLong,
+    #[msg("Only the auctioneer can call this instruction")]
+    NotAuctioneer,
+    #[msg("intent.timeout > clock.unix_timestamp")]
+    IntentNotExpired,
+    #[msg("Only the program upgrade authority can call this instruction")]
+    NotUpgradeAuthority,
+    #[msg("Intent dst_user is too long")]
+    DstUserTooLong,
+    #[msg("Single domain intent dst_user != src_user")]
+    DstUserMismatch,
+    #[msg("Intent auction already has a winner")]
+    AuctionAlreadyWon,
+    #[msg("intent.timeout < clock.unix_timestamp")]
+    IntentExpired,
+    #[msg("Cross-domain intents aren't supported yet")]
+    CrossDomainUnsupported,
+}
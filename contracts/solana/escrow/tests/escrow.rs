@@ -0,0 +1,601 @@
+use anchor_lang::prelude::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator, InstructionData, ToAccountMetas};
+use base64::Engine;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token::spl_token;
+use mantis_common::{Domain, EthereumNetwork, MantisNetwork, TokenAmount, UserIntent};
+use mantis_escrow_program::{
+    accounts, instruction, AuctionUpdated, Auctioneer, IntentCancelled, IntentEscrowed, IntentSettled,
+    IntentState, AUCTIONEER_SEED, INTENT_SEED,
+};
+use ruint::aliases::U256;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+
+const DECIMALS: u8 = 6;
+
+// anchor 0.29 entrypoints require `accounts` to outlive the call, which `processor!` can't express
+fn entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    mantis_escrow_program::entry(program_id, accounts, data)
+}
+
+struct Env {
+    ctx: ProgramTestContext,
+    /// Upgrade authority of the escrow program.
+    admin: Keypair,
+    user: Keypair,
+    mint: Pubkey,
+    user_token_account: Pubkey,
+    /// Log messages of the last processed transaction.
+    logs: Vec<String>,
+}
+
+/// A solver with token accounts for both sides of an intent.
+struct Solver {
+    keypair: Keypair,
+    token_out_mint: Pubkey,
+    token_in_account: Pubkey,
+    token_out_account: Pubkey,
+}
+
+impl Env {
+    async fn new(initial_balance: u64) -> Self {
+        let mut program_test = ProgramTest::new(
+            "mantis_escrow_program",
+            mantis_escrow_program::ID,
+            processor!(entry),
+        );
+        // `processor!` doesn't deploy through the upgradeable loader, so fake its program data
+        let admin = Keypair::new();
+        let program_data = UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(admin.pubkey()),
+        };
+        program_test.add_account(
+            program_data_address(),
+            Account {
+                lamports: 1_000_000_000,
+                data: bincode::serialize(&program_data).unwrap(),
+                owner: bpf_loader_upgradeable::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        let ctx = program_test.start_with_context().await;
+        let mut env = Env {
+            ctx,
+            admin,
+            user: Keypair::new(),
+            mint: Pubkey::default(),
+            user_token_account: Pubkey::default(),
+            logs: Vec::new(),
+        };
+
+        let user = env.user.pubkey();
+        env.fund(&user).await;
+        env.mint = env.create_mint().await;
+        env.user_token_account = env.create_token_account(env.mint, user, initial_balance).await;
+        env
+    }
+
+    async fn process(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        let blockhash = self.ctx.banks_client.get_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.ctx.payer];
+        all_signers.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&self.ctx.payer.pubkey()), &all_signers, blockhash);
+        let outcome = self.ctx.banks_client.process_transaction_with_metadata(tx).await?;
+        self.logs = outcome.metadata.map(|metadata| metadata.log_messages).unwrap_or_default();
+        outcome.result.map_err(BanksClientError::TransactionError)
+    }
+
+    /// Decodes the `T` events emitted by the last processed transaction.
+    fn events<T: AnchorDeserialize + Discriminator>(&self) -> Vec<T> {
+        self.logs
+            .iter()
+            .filter_map(|log| log.strip_prefix("Program data: "))
+            .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+            .filter(|data| data.starts_with(&T::DISCRIMINATOR))
+            .map(|data| T::deserialize(&mut &data[8..]).unwrap())
+            .collect()
+    }
+
+    async fn fund(&mut self, to: &Pubkey) {
+        let ix = system_instruction::transfer(&self.ctx.payer.pubkey(), to, 1_000_000_000);
+        self.process(&[ix], &[]).await.unwrap();
+    }
+
+    async fn create_mint(&mut self) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let payer = self.ctx.payer.pubkey();
+        let ixs = [
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint2(&spl_token::ID, &mint.pubkey(), &payer, None, DECIMALS)
+                .unwrap(),
+        ];
+        self.process(&ixs, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    async fn create_token_account(&mut self, mint: Pubkey, owner: Pubkey, amount: u64) -> Pubkey {
+        let account = Keypair::new();
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let payer = self.ctx.payer.pubkey();
+        let ixs = [
+            system_instruction::create_account(
+                &payer,
+                &account.pubkey(),
+                rent.minimum_balance(spl_token::state::Account::LEN),
+                spl_token::state::Account::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account3(&spl_token::ID, &account.pubkey(), &mint, &owner)
+                .unwrap(),
+            spl_token::instruction::mint_to(&spl_token::ID, &mint, &account.pubkey(), &payer, &[], amount)
+                .unwrap(),
+        ];
+        self.process(&ixs, &[&account]).await.unwrap();
+        account.pubkey()
+    }
+
+    async fn token_balance(&mut self, address: Pubkey) -> u64 {
+        let account = self.ctx.banks_client.get_account(address).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    async fn intent_state(&mut self, intent_id: &str) -> Option<IntentState> {
+        let account = self.ctx.banks_client.get_account(intent_address(intent_id)).await.unwrap()?;
+        Some(IntentState::try_deserialize(&mut account.data.as_slice()).unwrap())
+    }
+
+    fn user_intent(&self, amount_in: u64, token_out: &Pubkey, amount_out: u64) -> UserIntent {
+        UserIntent {
+            src_domain: Domain::Mantis(MantisNetwork::Testnet),
+            dst_domain: Domain::Mantis(MantisNetwork::Testnet),
+            src_user: self.user.pubkey().to_string(),
+            dst_user: self.user.pubkey().to_string(),
+            token_in: self.mint.to_string(),
+            amount_in: TokenAmount::new(U256::from(amount_in), DECIMALS),
+            token_out: token_out.to_string(),
+            amount_out: TokenAmount::new(U256::from(amount_out), DECIMALS),
+            timeout: u64::MAX,
+            nonce: 0,
+        }
+    }
+
+    /// Escrows `new_intent` under its derived id, returning that id.
+    async fn escrow(&mut self, amount: u64, new_intent: UserIntent) -> Result<String, BanksClientError> {
+        let intent_id = new_intent.intent_id();
+        self.escrow_with_id(&intent_id, amount, new_intent).await?;
+        Ok(intent_id)
+    }
+
+    async fn escrow_with_id(
+        &mut self,
+        intent_id: &str,
+        amount: u64,
+        new_intent: UserIntent,
+    ) -> Result<(), BanksClientError> {
+        let intent = intent_address(intent_id);
+        let ix = Instruction {
+            program_id: mantis_escrow_program::ID,
+            accounts: accounts::EscrowAndStoreIntent {
+                user: self.user.pubkey(),
+                intent,
+                token_in_mint: self.mint,
+                user_token_account: self.user_token_account,
+                escrow_token_account: vault_address(&intent, &self.mint),
+                token_program: spl_token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::EscrowAndStoreIntent {
+                intent_id: intent_id.to_string(),
+                amount,
+                new_intent,
+            }
+            .data(),
+        };
+        let user = self.user.insecure_clone();
+        self.process(&[ix], &[&user]).await
+    }
+
+    async fn initialize_auctioneer(&mut self, authority: &Keypair) -> Result<(), BanksClientError> {
+        self.fund(&authority.pubkey()).await;
+        let ix = Instruction {
+            program_id: mantis_escrow_program::ID,
+            accounts: accounts::InitializeAuctioneer {
+                authority: authority.pubkey(),
+                auctioneer: auctioneer_address(),
+                program_data: program_data_address(),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::InitializeAuctioneer {}.data(),
+        };
+        self.process(&[ix], &[authority]).await
+    }
+
+    async fn update_auction_data(
+        &mut self,
+        authority: &Keypair,
+        intent_id: &str,
+        amount_out: u128,
+        winner_solver: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = Instruction {
+            program_id: mantis_escrow_program::ID,
+            accounts: accounts::UpdateAuctionData {
+                authority: authority.pubkey(),
+                auctioneer: auctioneer_address(),
+                intent: intent_address(intent_id),
+            }
+            .to_account_metas(None),
+            data: instruction::UpdateAuctionData {
+                _intent_id: intent_id.to_string(),
+                amount_out,
+                winner_solver: winner_solver.to_string(),
+            }
+            .data(),
+        };
+        self.process(&[ix], &[authority]).await
+    }
+
+    /// Creates a funded solver holding `token_out_balance` of `token_out_mint` and an empty
+    /// account for the intent's input token.
+    async fn create_solver(&mut self, token_out_mint: Pubkey, token_out_balance: u64) -> Solver {
+        let keypair = Keypair::new();
+        self.fund(&keypair.pubkey()).await;
+        let token_in_account = self.create_token_account(self.mint, keypair.pubkey(), 0).await;
+        let token_out_account = self
+            .create_token_account(token_out_mint, keypair.pubkey(), token_out_balance)
+            .await;
+        Solver {
+            keypair,
+            token_out_mint,
+            token_in_account,
+            token_out_account,
+        }
+    }
+
+    async fn send_funds_to_user(
+        &mut self,
+        intent_id: &str,
+        solver: &Solver,
+        user_token_out_account: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let intent = intent_address(intent_id);
+        let ix = Instruction {
+            program_id: mantis_escrow_program::ID,
+            accounts: accounts::SendFundsToUser {
+                solver: solver.keypair.pubkey(),
+                user: self.user.pubkey(),
+                intent,
+                token_in_mint: self.mint,
+                escrow_token_account: vault_address(&intent, &self.mint),
+                solver_token_in_account: solver.token_in_account,
+                token_out_mint: solver.token_out_mint,
+                solver_token_out_account: solver.token_out_account,
+                user_token_out_account,
+                token_in_program: spl_token::ID,
+                token_out_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::SendFundsToUser {}.data(),
+        };
+        self.process(&[ix], &[&solver.keypair]).await
+    }
+
+    async fn cancel(&mut self, intent_id: &str) -> Result<(), BanksClientError> {
+        let intent = intent_address(intent_id);
+        let ix = Instruction {
+            program_id: mantis_escrow_program::ID,
+            accounts: accounts::UserCancelIntent {
+                user: self.user.pubkey(),
+                intent,
+                token_in_mint: self.mint,
+                escrow_token_account: vault_address(&intent, &self.mint),
+                user_token_account: self.user_token_account,
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::UserCancelIntent {}.data(),
+        };
+        let user = self.user.insecure_clone();
+        self.process(&[ix], &[&user]).await
+    }
+}
+
+fn intent_address(intent_id: &str) -> Pubkey {
+    Pubkey::find_program_address(&[INTENT_SEED, intent_id.as_bytes()], &mantis_escrow_program::ID).0
+}
+
+fn auctioneer_address() -> Pubkey {
+    Pubkey::find_program_address(&[AUCTIONEER_SEED], &mantis_escrow_program::ID).0
+}
+
+fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[mantis_escrow_program::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+fn vault_address(intent: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(intent, mint, &spl_token::ID)
+}
+
+#[tokio::test]
+async fn escrow_and_store_intent_moves_funds_to_vault() {
+    let mut env = Env::new(1_000).await;
+    let token_out = Pubkey::new_unique();
+    let new_intent = env.user_intent(400, &token_out, 500);
+    let intent_id = env.escrow(400, new_intent).await.unwrap();
+
+    let vault = vault_address(&intent_address(&intent_id), &env.mint);
+    assert_eq!(env.token_balance(vault).await, 400);
+    let user_token_account = env.user_token_account;
+    assert_eq!(env.token_balance(user_token_account).await, 600);
+
+    let state = env.intent_state(&intent_id).await.unwrap();
+    assert_eq!(state.intent_id, intent_id);
+    assert_eq!(state.user, env.user.pubkey());
+    assert_eq!(state.dst_domain, "mantis-testnet");
+    assert_eq!(state.dst_user, env.user.pubkey().to_string());
+    assert_eq!(state.token_in, env.mint);
+    assert_eq!(state.amount_in, 400);
+    assert_eq!(state.token_out, token_out.to_string());
+    assert_eq!(state.amount_out, 500);
+    assert!(state.winner_solver.is_empty());
+    assert_eq!(state.timeout, u64::MAX);
+
+    let events = env.events::<IntentEscrowed>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].intent_id, intent_id);
+    assert_eq!(events[0].user, env.user.pubkey());
+    assert_eq!(events[0].amount_in, 400);
+    assert_eq!(events[0].amount_out, 500);
+}
+
+#[tokio::test]
+async fn escrow_and_store_intent_rejects_cross_domain_intents() {
+    let mut env = Env::new(1_000).await;
+    let mut new_intent = env.user_intent(400, &Pubkey::new_unique(), 500);
+    new_intent.dst_domain = Domain::Ethereum(EthereumNetwork::Mainnet);
+    new_intent.dst_user = "0x39F98f32eb5fe4C568c7252e45fd48f8DC415d8e".to_string();
+    new_intent.token_out = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string();
+    new_intent.timeout = 0;
+    let intent_id = new_intent.intent_id();
+    assert!(env.escrow(400, new_intent).await.is_err());
+    assert!(env.intent_state(&intent_id).await.is_none());
+
+    // nothing was escrowed, so an expired cross-domain intent can't be refunded directly
+    assert!(env.cancel(&intent_id).await.is_err());
+    let user_token_account = env.user_token_account;
+    assert_eq!(env.token_balance(user_token_account).await, 1_000);
+}
+
+#[tokio::test]
+async fn escrow_and_store_intent_rejects_single_domain_foreign_dst_user() {
+    let mut env = Env::new(1_000).await;
+    let mut new_intent = env.user_intent(400, &Pubkey::new_unique(), 500);
+    new_intent.dst_user = Pubkey::new_unique().to_string();
+    assert!(env.escrow(400, new_intent).await.is_err());
+}
+
+#[tokio::test]
+async fn escrow_and_store_intent_rejects_foreign_user() {
+    let mut env = Env::new(1_000).await;
+    let mut new_intent = env.user_intent(400, &Pubkey::new_unique(), 500);
+    new_intent.src_user = Pubkey::new_unique().to_string();
+    assert!(env.escrow(400, new_intent).await.is_err());
+}
+
+#[tokio::test]
+async fn escrow_and_store_intent_rejects_wrong_decimals() {
+    let mut env = Env::new(1_000).await;
+    let mut new_intent = env.user_intent(400, &Pubkey::new_unique(), 500);
+    new_intent.amount_in = TokenAmount::new(U256::from(400), DECIMALS + 1);
+    assert!(env.escrow(400, new_intent).await.is_err());
+}
+
+#[tokio::test]
+async fn escrow_and_store_intent_rejects_duplicate_id() {
+    let mut env = Env::new(1_000).await;
+    let new_intent = env.user_intent(100, &Pubkey::new_unique(), 500);
+    let intent_id = env.escrow(100, new_intent.clone()).await.unwrap();
+    assert!(env.escrow_with_id(&intent_id, 100, new_intent).await.is_err());
+}
+
+#[tokio::test]
+async fn escrow_and_store_intent_rejects_underived_id() {
+    let mut env = Env::new(1_000).await;
+    let new_intent = env.user_intent(400, &Pubkey::new_unique(), 500);
+    assert!(env.escrow_with_id("intent-1", 400, new_intent).await.is_err());
+}
+
+#[tokio::test]
+async fn initialize_auctioneer_requires_upgrade_authority() {
+    let mut env = Env::new(1_000).await;
+
+    let impostor = Keypair::new();
+    assert!(env.initialize_auctioneer(&impostor).await.is_err());
+    let auctioneer = env.ctx.banks_client.get_account(auctioneer_address()).await.unwrap();
+    assert!(auctioneer.is_none());
+
+    let admin = env.admin.insecure_clone();
+    env.initialize_auctioneer(&admin).await.unwrap();
+    let account = env.ctx.banks_client.get_account(auctioneer_address()).await.unwrap().unwrap();
+    let auctioneer = Auctioneer::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(auctioneer.authority, admin.pubkey());
+}
+
+#[tokio::test]
+async fn update_auction_data_requires_auctioneer_authority() {
+    let mut env = Env::new(1_000).await;
+    let new_intent = env.user_intent(400, &Pubkey::new_unique(), 500);
+    let intent_id = env.escrow(400, new_intent).await.unwrap();
+
+    let authority = env.admin.insecure_clone();
+    env.initialize_auctioneer(&authority).await.unwrap();
+    let solver = Pubkey::new_unique();
+
+    let impostor = Keypair::new();
+    assert!(env.update_auction_data(&impostor, &intent_id, 450, &solver).await.is_err());
+
+    env.update_auction_data(&authority, &intent_id, 450, &solver).await.unwrap();
+    let events = env.events::<AuctionUpdated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].intent_id, intent_id);
+    assert_eq!(events[0].amount_out, 450);
+    assert_eq!(events[0].winner_solver, solver.to_string());
+    let state = env.intent_state(&intent_id).await.unwrap();
+    assert_eq!(state.amount_out, 450);
+    assert_eq!(state.winner_solver, solver.to_string());
+}
+
+#[tokio::test]
+async fn update_auction_data_rejects_a_second_winner() {
+    let mut env = Env::new(1_000).await;
+    let new_intent = env.user_intent(400, &Pubkey::new_unique(), 500);
+    let intent_id = env.escrow(400, new_intent).await.unwrap();
+
+    let authority = env.admin.insecure_clone();
+    env.initialize_auctioneer(&authority).await.unwrap();
+    let winner = Pubkey::new_unique();
+    env.update_auction_data(&authority, &intent_id, 450, &winner).await.unwrap();
+
+    let other = Pubkey::new_unique();
+    assert!(env.update_auction_data(&authority, &intent_id, 460, &other).await.is_err());
+    let state = env.intent_state(&intent_id).await.unwrap();
+    assert_eq!(state.amount_out, 450);
+    assert_eq!(state.winner_solver, winner.to_string());
+}
+
+#[tokio::test]
+async fn update_auction_data_rejects_expired_intents() {
+    let mut env = Env::new(1_000).await;
+    let mut new_intent = env.user_intent(400, &Pubkey::new_unique(), 500);
+    new_intent.timeout = 0;
+    let intent_id = env.escrow(400, new_intent).await.unwrap();
+
+    let authority = env.admin.insecure_clone();
+    env.initialize_auctioneer(&authority).await.unwrap();
+    let solver = Pubkey::new_unique();
+    assert!(env.update_auction_data(&authority, &intent_id, 450, &solver).await.is_err());
+    let state = env.intent_state(&intent_id).await.unwrap();
+    assert!(state.winner_solver.is_empty());
+}
+
+#[tokio::test]
+async fn send_funds_to_user_settles_and_closes_intent() {
+    let mut env = Env::new(1_000).await;
+    let token_out_mint = env.create_mint().await;
+    let user = env.user.pubkey();
+    let user_token_out_account = env.create_token_account(token_out_mint, user, 0).await;
+    let new_intent = env.user_intent(400, &token_out_mint, 500);
+    let intent_id = env.escrow(400, new_intent).await.unwrap();
+
+    let solver = env.create_solver(token_out_mint, 1_000).await;
+    let authority = env.admin.insecure_clone();
+    env.initialize_auctioneer(&authority).await.unwrap();
+    env.update_auction_data(&authority, &intent_id, 450, &solver.keypair.pubkey()).await.unwrap();
+
+    env.send_funds_to_user(&intent_id, &solver, user_token_out_account).await.unwrap();
+
+    let events = env.events::<IntentSettled>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].intent_id, intent_id);
+    assert_eq!(events[0].solver, solver.keypair.pubkey());
+    assert_eq!(events[0].amount_out, 450);
+    assert_eq!(env.token_balance(user_token_out_account).await, 450);
+    assert_eq!(env.token_balance(solver.token_out_account).await, 550);
+    assert_eq!(env.token_balance(solver.token_in_account).await, 400);
+    assert!(env.intent_state(&intent_id).await.is_none());
+    let vault = vault_address(&intent_address(&intent_id), &env.mint);
+    assert!(env.ctx.banks_client.get_account(vault).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn send_funds_to_user_rejects_non_winner_solver() {
+    let mut env = Env::new(1_000).await;
+    let token_out_mint = env.create_mint().await;
+    let user = env.user.pubkey();
+    let user_token_out_account = env.create_token_account(token_out_mint, user, 0).await;
+    let new_intent = env.user_intent(400, &token_out_mint, 500);
+    let intent_id = env.escrow(400, new_intent).await.unwrap();
+
+    let winner = env.create_solver(token_out_mint, 1_000).await;
+    let other = env.create_solver(token_out_mint, 1_000).await;
+    let authority = env.admin.insecure_clone();
+    env.initialize_auctioneer(&authority).await.unwrap();
+    env.update_auction_data(&authority, &intent_id, 450, &winner.keypair.pubkey()).await.unwrap();
+
+    assert!(env.send_funds_to_user(&intent_id, &other, user_token_out_account).await.is_err());
+    assert_eq!(env.token_balance(user_token_out_account).await, 0);
+    assert_eq!(env.token_balance(other.token_in_account).await, 0);
+    assert!(env.intent_state(&intent_id).await.is_some());
+}
+
+#[tokio::test]
+async fn send_funds_to_user_rejects_wrong_token_out_mint() {
+    let mut env = Env::new(1_000).await;
+    let token_out_mint = env.create_mint().await;
+    let new_intent = env.user_intent(400, &token_out_mint, 500);
+    let intent_id = env.escrow(400, new_intent).await.unwrap();
+
+    // the solver pays out a token other than the intent's token_out
+    let wrong_mint = env.create_mint().await;
+    let user = env.user.pubkey();
+    let user_wrong_account = env.create_token_account(wrong_mint, user, 0).await;
+    let solver = env.create_solver(wrong_mint, 1_000).await;
+    let authority = env.admin.insecure_clone();
+    env.initialize_auctioneer(&authority).await.unwrap();
+    env.update_auction_data(&authority, &intent_id, 450, &solver.keypair.pubkey()).await.unwrap();
+
+    assert!(env.send_funds_to_user(&intent_id, &solver, user_wrong_account).await.is_err());
+    assert_eq!(env.token_balance(user_wrong_account).await, 0);
+    assert_eq!(env.token_balance(solver.token_in_account).await, 0);
+    assert!(env.intent_state(&intent_id).await.is_some());
+}
+
+#[tokio::test]
+async fn user_cancel_intent_refunds_after_timeout() {
+    let mut env = Env::new(1_000).await;
+    let mut new_intent = env.user_intent(400, &Pubkey::new_unique(), 500);
+    new_intent.timeout = 0;
+    let intent_id = env.escrow(400, new_intent).await.unwrap();
+
+    env.cancel(&intent_id).await.unwrap();
+
+    let events = env.events::<IntentCancelled>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].intent_id, intent_id);
+    assert_eq!(events[0].user, env.user.pubkey());
+    let user_token_account = env.user_token_account;
+    assert_eq!(env.token_balance(user_token_account).await, 1_000);
+    assert!(env.intent_state(&intent_id).await.is_none());
+}
+
+#[tokio::test]
+async fn user_cancel_intent_rejects_before_timeout() {
+    let mut env = Env::new(1_000).await;
+    let new_intent = env.user_intent(400, &Pubkey::new_unique(), 500);
+    let intent_id = env.escrow(400, new_intent).await.unwrap();
+
+    assert!(env.cancel(&intent_id).await.is_err());
+    assert!(env.intent_state(&intent_id).await.is_some());
+}
@@ -1,8 +1,13 @@
-use crate::Chain;
-use alloy::primitives::Address;
-use anchor_spl::token_2022::spl_token_2022::solana_program::pubkey::Pubkey;
+use crate::ethereum::Settlement;
+use crate::quote::{HttpQuoteProvider, Quote, QuoteProvider, QuoteRequest};
+use crate::tokens::TokenRegistry;
+use crate::{CancelOutcome, Chain, SimulationReport, SubmitReceipt};
+use anyhow::anyhow;
 use clap::{Args, FromArgMatches, Parser, Subcommand};
-use mantis_common::UserIntent;
+use mantis_common::{Domain, IntentStatus, TokenAmount, TokenAmountError, UserIntent};
+use reqwest::Url;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Parser)]
 #[command(name = "mantis-cli", version = "0.1.0", author = "CF Services")]
@@ -16,17 +21,14 @@ impl Cli {
         match self.command {
             Commands::Intent { action } => match action {
                 IntentActions::Submit(cmd) => cmd.run().await,
+                IntentActions::Status(cmd) => cmd.run().await,
+                IntentActions::Watch(cmd) => cmd.run().await,
+                IntentActions::Cancel(cmd) => cmd.run().await,
             },
-            Commands::GetQuote(args) => {
-                println!("Getting quote:");
-                println!("Token In: {}", args.token_in_name);
-                println!("Token Out: {}", args.token_out_name);
-                println!("Amount In: {}", args.amount_in);
-                // Placeholder for get_quote function
-                // let res = get_quote(&args.token_in_name, &args.token_out_name, &args.amount_in).await;
-                // println!("{}", res.unwrap());
-                Ok(())
-            }
+            Commands::Solver { action } => match action {
+                SolverActions::Settle(cmd) => cmd.run().await,
+            },
+            Commands::GetQuote(cmd) => cmd.run().await,
         }
     }
 }
@@ -38,14 +40,171 @@ enum Commands {
         #[command(subcommand)]
         action: IntentActions,
     },
+    /// Act as a solver
+    Solver {
+        #[command(subcommand)]
+        action: SolverActions,
+    },
     /// Get a quote for token exchange
     GetQuote(GetQuoteCmd),
 }
 
+#[derive(Subcommand)]
+enum SolverActions {
+    /// Settle an intent won by the signer through the Ethereum escrow
+    Settle(SolverSettleCmd),
+}
+
 #[derive(Subcommand)]
 enum IntentActions {
     /// Submit an intent to exchange tokens
     Submit(SubmitIntentCmd),
+    /// Show the status of an intent
+    Status(IntentStatusCmd),
+    /// Poll the status of an intent until it's settled or cancelled
+    Watch(WatchIntentCmd),
+    /// Cancel a timed out intent and get the escrowed tokens back
+    Cancel(CancelIntentCmd),
+}
+
+#[derive(Args)]
+struct IntentIdArgs<T>
+where
+    T: FromArgMatches + Args,
+{
+    #[arg(value_name = "INTENT_ID")]
+    intent_id: String,
+    #[command(flatten)]
+    additional: T,
+}
+
+#[derive(Subcommand)]
+enum IntentNetworkCmd {
+    /// Intent on Solana network
+    Solana(IntentIdArgs<crate::solana::cli::SolanaArgs>),
+    /// Intent on Ethereum network
+    Ethereum(IntentIdArgs<crate::ethereum::cli::EthereumArgs>),
+}
+
+#[derive(Args)]
+struct IntentStatusCmd {
+    #[command(subcommand)]
+    network: IntentNetworkCmd,
+}
+
+impl IntentStatusCmd {
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        match self.network {
+            IntentNetworkCmd::Solana(args) => {
+                let solana_client = args.additional.build_client().await?;
+                print_status(&args.intent_id, &solana_client.intent_status(&args.intent_id).await?);
+            }
+            IntentNetworkCmd::Ethereum(args) => {
+                let eth_client = args.additional.build_client().await?;
+                print_status(&args.intent_id, &eth_client.intent_status(&args.intent_id).await?);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+struct WatchIntentCmd {
+    /// Seconds between status checks
+    #[arg(long, default_value_t = 5)]
+    interval: u64,
+    #[command(subcommand)]
+    network: IntentNetworkCmd,
+}
+
+impl WatchIntentCmd {
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        let interval = Duration::from_secs(self.interval);
+        match self.network {
+            IntentNetworkCmd::Solana(args) => {
+                let solana_client = args.additional.build_client().await?;
+                watch_intent(&solana_client, &args.intent_id, interval).await
+            }
+            IntentNetworkCmd::Ethereum(args) => {
+                let eth_client = args.additional.build_client().await?;
+                watch_intent(&eth_client, &args.intent_id, interval).await
+            }
+        }
+    }
+}
+
+#[derive(Args)]
+struct CancelIntentCmd {
+    /// Simulate the transaction instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
+    /// Domain the intent pays out on, as submitted. Defaults to the source chain on Solana,
+    /// required on Ethereum since its escrow doesn't record it
+    #[arg(long, global = true)]
+    dst_domain: Option<Domain>,
+    #[command(subcommand)]
+    network: IntentNetworkCmd,
+}
+
+impl CancelIntentCmd {
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        let (intent_id, receipt) = match self.network {
+            IntentNetworkCmd::Solana(args) => {
+                let solana_client = args.additional.build_client().await?;
+                let dst_domain = self.dst_domain.unwrap_or(Domain::from(args.additional.cluster));
+                if self.dry_run {
+                    let report = solana_client.simulate_cancel_intent(&args.intent_id, dst_domain).await?;
+                    return report_simulation(&report);
+                }
+                let receipt = solana_client.cancel_intent(&args.intent_id, dst_domain).await?;
+                (args.intent_id, receipt)
+            }
+            IntentNetworkCmd::Ethereum(args) => {
+                let dst_domain = self
+                    .dst_domain
+                    .ok_or_else(|| anyhow!("--dst-domain is required, the Ethereum escrow doesn't record it"))?;
+                let eth_client = args.additional.build_client().await?;
+                if self.dry_run {
+                    let report = eth_client.simulate_cancel_intent(&args.intent_id, dst_domain).await?;
+                    return report_simulation(&report);
+                }
+                let receipt = eth_client.cancel_intent(&args.intent_id, dst_domain).await?;
+                (args.intent_id, receipt)
+            }
+        };
+        match receipt.outcome {
+            CancelOutcome::Refunded => println!("Intent {} cancelled, escrowed tokens refunded", intent_id),
+            CancelOutcome::CrossChainMsgUser => println!(
+                "Intent {} cancelled cross-domain, escrowed tokens are refunded once the bridge confirms",
+                intent_id
+            ),
+            CancelOutcome::AlreadyRequested => println!(
+                "Refund of intent {} was already requested, escrowed tokens are refunded once the bridge confirms",
+                intent_id
+            ),
+        }
+        println!("Transaction hash: {}", receipt.tx_hash);
+        Ok(())
+    }
+}
+
+/// Prints the status of the intent whenever it changes, until it's settled or cancelled.
+async fn watch_intent<C: Chain>(chain: &C, intent_id: &str, interval: Duration) -> anyhow::Result<()>
+where
+    anyhow::Error: From<C::Error>,
+{
+    let mut last_status = None;
+    loop {
+        let status = chain.intent_status(intent_id).await?;
+        if last_status.as_ref() != Some(&status) {
+            print_status(intent_id, &status);
+        }
+        if status.is_terminal() {
+            return Ok(());
+        }
+        last_status = Some(status);
+        tokio::time::sleep(interval).await;
+    }
 }
 
 #[derive(Args)]
@@ -69,6 +228,80 @@ struct TokenSwapArgs {
     token_out_name: String,
     #[arg(value_name = "AMOUNT_OUT")]
     amount_out: String,
+    /// Seconds from now after which the intent can be cancelled
+    #[arg(long, default_value_t = 3600)]
+    timeout: u64,
+    /// Take AMOUNT_IN and AMOUNT_OUT in base units (wei, lamports) instead of whole tokens
+    #[arg(long)]
+    base_units: bool,
+    /// TOML or JSON file with tokens to add to the built-in token registry
+    #[arg(long, env = "MANTIS_TOKEN_REGISTRY")]
+    token_registry: Option<PathBuf>,
+    /// Domain to receive TOKEN_OUT on, e.g. solana-mainnet. Defaults to the source chain
+    #[arg(long)]
+    dst_domain: Option<Domain>,
+    /// Receiver of TOKEN_OUT. Defaults to the signer
+    #[arg(long)]
+    dst_user: Option<String>,
+}
+
+impl TokenSwapArgs {
+    /// Returns the unix timestamp at which the intent times out.
+    fn timeout_timestamp(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time is before unix epoch")
+            .as_secs();
+        now + self.timeout
+    }
+
+    /// Builds the intent for `chain`, resolving token symbols through the token registry.
+    async fn user_intent<C: Chain>(&self, chain: &C, src_domain: Domain) -> anyhow::Result<UserIntent>
+    where
+        C::Address: ToString,
+        anyhow::Error: From<C::Error>,
+    {
+        let dst_domain = self.dst_domain.unwrap_or(src_domain);
+        let src_user = chain.signer().to_string();
+        let dst_user = match &self.dst_user {
+            Some(dst_user) => dst_user.clone(),
+            None if dst_domain == src_domain => src_user.clone(),
+            None => return Err(anyhow!("--dst-user is required for cross domain intents")),
+        };
+
+        let registry = load_token_registry(self.token_registry.as_deref())?;
+        let (token_in, decimals_in) = resolve_token(&registry, chain, src_domain, &self.token_in_name).await?;
+        let (token_out, decimals_out) = if dst_domain == src_domain {
+            resolve_token(&registry, chain, src_domain, &self.token_out_name).await?
+        } else {
+            // tokens on other domains can't be looked up through `chain`
+            let info = registry.get(&self.token_out_name, dst_domain).ok_or_else(|| {
+                anyhow!("Token {} is not in the token registry for {}", self.token_out_name, dst_domain)
+            })?;
+            (info.cross_chain_denom(), info.decimals)
+        };
+
+        Ok(UserIntent {
+            src_domain,
+            dst_domain,
+            src_user,
+            dst_user,
+            token_in,
+            amount_in: self.parse_amount(&self.amount_in, decimals_in)?,
+            token_out,
+            amount_out: self.parse_amount(&self.amount_out, decimals_out)?,
+            timeout: self.timeout_timestamp(),
+            nonce: nonce(),
+        })
+    }
+
+    fn parse_amount(&self, amount: &str, decimals: u8) -> Result<TokenAmount, TokenAmountError> {
+        if self.base_units {
+            TokenAmount::parse_base_units(amount, decimals)
+        } else {
+            TokenAmount::parse_units(amount, decimals)
+        }
+    }
 }
 
 #[derive(Args)]
@@ -79,6 +312,59 @@ struct GetQuoteCmd {
     token_out_name: String,
     #[arg(value_name = "AMOUNT_IN")]
     amount_in: String,
+    /// Quote endpoint of the Mantis auctioneer
+    #[arg(long, env = "MANTIS_QUOTE_URL")]
+    quote_url: Url,
+    /// Chain to quote on, e.g. ethereum-mainnet or solana-mainnet. Tokens are then resolved
+    /// to their addresses there and amounts are sent in base units
+    #[arg(long)]
+    chain: Option<Domain>,
+    /// TOML or JSON file with tokens to add to the built-in token registry
+    #[arg(long, env = "MANTIS_TOKEN_REGISTRY")]
+    token_registry: Option<PathBuf>,
+}
+
+impl GetQuoteCmd {
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        let provider = HttpQuoteProvider::new(self.quote_url.clone());
+
+        println!(
+            "Getting quote for {} {} to {}",
+            self.amount_in, self.token_in_name, self.token_out_name
+        );
+        let (request, decimals_out) = match self.chain {
+            Some(chain) => {
+                let registry = load_token_registry(self.token_registry.as_deref())?;
+                let unknown =
+                    |symbol: &str| anyhow!("Token {} is not in the token registry for {}", symbol, chain);
+                let token_in = registry
+                    .get(&self.token_in_name, chain)
+                    .ok_or_else(|| unknown(&self.token_in_name))?;
+                let token_out = registry
+                    .get(&self.token_out_name, chain)
+                    .ok_or_else(|| unknown(&self.token_out_name))?;
+                let amount_in = TokenAmount::parse_units(&self.amount_in, token_in.decimals)?;
+                let request = QuoteRequest {
+                    token_in: token_in.address.clone(),
+                    token_out: token_out.address.clone(),
+                    amount_in: amount_in.raw().to_string(),
+                };
+                (request, Some(token_out.decimals))
+            }
+            None => {
+                let request = QuoteRequest {
+                    token_in: self.token_in_name.clone(),
+                    token_out: self.token_out_name.clone(),
+                    amount_in: self.amount_in.clone(),
+                };
+                (request, None)
+            }
+        };
+
+        let quote = provider.get_quote(&request).await?;
+        print_quote(&self.token_in_name, &self.token_out_name, &quote, decimals_out)?;
+        Ok(())
+    }
 }
 
 #[derive(Subcommand)]
@@ -100,13 +386,16 @@ impl SubmitIntentNetworkCmd {
 
 #[derive(Args)]
 struct SubmitIntentCmd {
+    /// Simulate the transaction instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
     #[command(subcommand)]
     network: SubmitIntentNetworkCmd,
 }
 
 impl SubmitIntentCmd {
     pub(crate) async fn run(self) -> anyhow::Result<()> {
-        let SubmitIntentCmd { network } = self;
+        let SubmitIntentCmd { dry_run, network } = self;
 
         let exchange_args = network.swap_args();
         match &network {
@@ -126,15 +415,14 @@ impl SubmitIntentCmd {
                 println!("Using keypair: {}", solana_args.mnemonic);
                 let solana_client = solana_args.build_client().await?;
 
-                let user_intent = UserIntent {
-                    token_in: exchange_args.token_in_name.clone(),
-                    amount_in: exchange_args.amount_in.clone(),
-                    token_out: exchange_args.token_out_name.clone(),
-                    amount_out: exchange_args.amount_out.clone(),
-                    user_address: solana_client.signer().to_string(),
-                };
-                let address = Pubkey::default();
-                solana_client.submit_intent(user_intent, address).await?;
+                let user_intent = exchange_args
+                    .user_intent(&solana_client, solana_args.cluster.into())
+                    .await?;
+                if dry_run {
+                    return report_simulation(&solana_client.simulate_submit_intent(user_intent).await?);
+                }
+                let receipt = solana_client.submit_intent(user_intent).await?;
+                print_receipt(&receipt);
                 Ok(())
             }
             SubmitIntentNetworkCmd::Ethereum(IntentSubmitArgs {
@@ -143,17 +431,171 @@ impl SubmitIntentCmd {
             }) => {
                 let eth_client = eth_args.build_client().await?;
 
-                let user_intent = UserIntent {
-                    token_in: exchange_args.token_in_name.clone(),
-                    amount_in: exchange_args.amount_in.clone(),
-                    token_out: exchange_args.token_out_name.clone(),
-                    amount_out: exchange_args.amount_out.clone(),
-                    user_address: eth_client.signer().to_string(),
-                };
-                let address = Address::default();
-                eth_client.submit_intent(user_intent, address).await?;
+                let user_intent = exchange_args
+                    .user_intent(&eth_client, eth_args.network.into())
+                    .await?;
+                if dry_run {
+                    return report_simulation(&eth_client.simulate_submit_intent(user_intent).await?);
+                }
+                let receipt = eth_client.submit_intent(user_intent).await?;
+                print_receipt(&receipt);
                 Ok(())
             }
         }
     }
 }
+
+#[derive(Args)]
+struct SolverSettleCmd {
+    #[arg(value_name = "INTENT_ID")]
+    intent_id: String,
+    #[command(flatten)]
+    src_args: crate::solana::cli::SolanaSourceArgs,
+    /// Address on the source cluster receiving the escrowed input tokens
+    #[arg(long, requires = "src_cluster")]
+    solver_out: Option<String>,
+    /// Simulate the transaction instead of sending it
+    #[arg(long)]
+    dry_run: bool,
+    #[command(flatten)]
+    eth_args: crate::ethereum::cli::EthereumArgs,
+}
+
+impl SolverSettleCmd {
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        let eth_client = self.eth_args.build_client().await?;
+
+        let source = (self.src_args.cluster, self.src_args.build_reader());
+        let settlement = match (source, self.solver_out) {
+            ((Some(cluster), Some(solana_client)), Some(solver_out)) => {
+                let intent = solana_client
+                    .get_intent(&self.intent_id)
+                    .await?
+                    .ok_or_else(|| anyhow!("Intent {} not found on {:?}", self.intent_id, cluster))?;
+                Settlement::CrossChain { intent, solver_out }
+            }
+            _ => Settlement::SingleDomain {
+                intent_id: self.intent_id.clone(),
+            },
+        };
+        if self.dry_run {
+            return report_simulation(&eth_client.simulate_send_funds_to_user(settlement).await?);
+        }
+        let receipt = eth_client.send_funds_to_user(settlement).await?;
+        println!("Intent {} settled", self.intent_id);
+        println!("Transaction hash: {}", receipt.tx_hash);
+        if let Some(block_number) = receipt.block_number {
+            println!("Block: {}", block_number);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the built-in token registry, extended with the tokens in `path` if given.
+fn load_token_registry(path: Option<&Path>) -> anyhow::Result<TokenRegistry> {
+    let mut registry = TokenRegistry::default();
+    if let Some(path) = path {
+        registry.extend(TokenRegistry::from_file(path)?);
+    }
+    Ok(registry)
+}
+
+/// Resolves a token symbol or address to its address on `domain` and its decimals.
+///
+/// Symbols are looked up in `registry`, anything else is taken as an address and its
+/// decimals are read from chain.
+async fn resolve_token<C: Chain>(
+    registry: &TokenRegistry,
+    chain: &C,
+    domain: Domain,
+    token: &str,
+) -> anyhow::Result<(String, u8)>
+where
+    anyhow::Error: From<C::Error>,
+{
+    if let Some(info) = registry.get(token, domain) {
+        return Ok((info.address.clone(), info.decimals));
+    }
+    let decimals = chain.token_decimals(token).await.map_err(|e| {
+        anyhow::Error::from(e).context(format!(
+            "{} is neither a known token on {} nor a token address",
+            token, domain
+        ))
+    })?;
+    Ok((token.to_string(), decimals))
+}
+
+/// Returns a nonce for a new intent, unique per user for all practical purposes.
+fn nonce() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before unix epoch")
+        .as_nanos() as u64
+}
+
+fn print_receipt(receipt: &SubmitReceipt) {
+    println!("Intent submitted: {}", receipt.intent_id);
+    println!("Transaction: {}", receipt.tx_hash);
+    if let Some(block_number) = receipt.block_number {
+        println!("Block: {}", block_number);
+    }
+}
+
+/// Prints the outcome of a dry run, failing if the transaction would fail.
+fn report_simulation(report: &SimulationReport) -> anyhow::Result<()> {
+    for log in &report.logs {
+        println!("  {}", log);
+    }
+    if let Some(token) = &report.approval {
+        if report.escrow_call_verified {
+            println!("The escrow's allowance of {} is too low, simulated as if it was approved", token);
+        } else {
+            println!("The escrow's allowance of {} is too low, only simulated its approval", token);
+        }
+    }
+    if !report.success {
+        let error = report.error.as_deref().unwrap_or("unknown error");
+        return Err(anyhow!("Transaction would fail: {}", error));
+    }
+    if report.escrow_call_verified {
+        println!("Simulation succeeded, nothing was sent");
+    } else {
+        println!("Approval simulation succeeded, the escrow call wasn't verified. Nothing was sent");
+    }
+    if let Some(gas_used) = report.gas_used {
+        println!("Gas used: {}", gas_used);
+    }
+    Ok(())
+}
+
+fn print_status(intent_id: &str, status: &IntentStatus) {
+    match status {
+        IntentStatus::Escrowed => println!("Intent {}: escrowed, waiting for the auction", intent_id),
+        IntentStatus::Auctioned { winner_solver } => {
+            println!("Intent {}: auctioned, won by {}", intent_id, winner_solver)
+        }
+        IntentStatus::Settled => println!("Intent {}: settled", intent_id),
+        IntentStatus::Cancelled => println!("Intent {}: cancelled", intent_id),
+        IntentStatus::Expired => println!("Intent {}: expired, can be cancelled", intent_id),
+    }
+}
+
+/// Prints `quote`, formatting `amount_out` in whole tokens if its decimals are known.
+fn print_quote(token_in: &str, token_out: &str, quote: &Quote, decimals_out: Option<u8>) -> anyhow::Result<()> {
+    let amount_out = match decimals_out {
+        Some(decimals) => TokenAmount::parse_base_units(&quote.amount_out, decimals)?.to_string(),
+        None => quote.amount_out.clone(),
+    };
+    println!("Amount Out: {} {}", amount_out, token_out);
+    println!("Price: {} {} per {}", quote.price, token_out, token_in);
+    for fee in &quote.fees {
+        println!("Fee ({}): {} {}", fee.kind, fee.amount, fee.token);
+    }
+    if !quote.route.is_empty() {
+        println!("Route:");
+        for hop in &quote.route {
+            println!("  {} -> {} on {}", hop.token_in, hop.token_out, hop.venue);
+        }
+    }
+    Ok(())
+}
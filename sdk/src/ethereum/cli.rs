@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use alloy::primitives::Address;
 use alloy::signers::k256::ecdsa;
 use alloy::signers::local::coins_bip39::{English, Mnemonic};
 use alloy::signers::local::PrivateKeySigner;
@@ -6,6 +7,9 @@ use super::{ChainError, Network};
 use clap::Args;
 use reqwest::Url;
 use crate::ethereum::client::EthereumClient;
+use crate::ethereum::erc20::Approval;
+use crate::ethereum::fees::{EthFeeConfig, FeeMode, MIN_FEE_BUMP_PERCENT};
+use std::time::Duration;
 
 // TODO: improve keypair handling (load from raw keys, files, interactive, etc.)
 #[derive(Args)]
@@ -22,6 +26,49 @@ pub(crate) struct EthereumArgs {
     pub(crate) ws_url: Option<Url>,
     #[arg(long, env = "ETHEREUM_CLUSTER")]
     pub(crate) network: Network,
+    /// Escrow contract address
+    #[arg(long, env = "ETHEREUM_ESCROW_ADDRESS")]
+    pub(crate) escrow_address: Address,
+    /// Block the escrow contract was deployed in, where searches of its past logs start
+    #[arg(long, env = "ETHEREUM_ESCROW_DEPLOYMENT_BLOCK", default_value_t = 0)]
+    pub(crate) escrow_deployment_block: u64,
+    /// How much to approve when the escrow's token allowance is too low
+    #[arg(long, value_enum, default_value_t = Approval::Exact)]
+    pub(crate) approval: Approval,
+    /// How transaction fees are priced
+    #[arg(long, value_enum, env = "ETHEREUM_FEE_MODE", default_value_t = FeeMode::Eip1559)]
+    pub(crate) fee_mode: FeeMode,
+    /// Cap of the max fee per gas (the gas price in legacy mode), in wei
+    #[arg(long, env = "ETHEREUM_MAX_FEE_PER_GAS")]
+    pub(crate) max_fee_per_gas: Option<u128>,
+    /// Cap of the max priority fee per gas, in wei
+    #[arg(long, env = "ETHEREUM_MAX_PRIORITY_FEE_PER_GAS")]
+    pub(crate) max_priority_fee_per_gas: Option<u128>,
+    /// Factor the estimated gas limit is multiplied with, at least 1
+    #[arg(
+        long,
+        env = "ETHEREUM_GAS_LIMIT_MULTIPLIER",
+        default_value_t = 1.2,
+        value_parser = parse_gas_limit_multiplier
+    )]
+    pub(crate) gas_limit_multiplier: f64,
+    /// Blocks a transaction must be included in before it counts as confirmed
+    #[arg(long, env = "ETHEREUM_CONFIRMATIONS", default_value_t = 1)]
+    pub(crate) confirmations: u64,
+    /// Seconds to wait for a transaction to be included before replacing it with higher fees
+    #[arg(long, env = "ETHEREUM_TX_TIMEOUT", default_value_t = 60)]
+    pub(crate) tx_timeout: u64,
+    /// Percentage fees are raised by when replacing a stuck transaction, at least 10
+    #[arg(
+        long,
+        env = "ETHEREUM_FEE_BUMP_PERCENT",
+        default_value_t = 20,
+        value_parser = clap::value_parser!(u64).range(MIN_FEE_BUMP_PERCENT..)
+    )]
+    pub(crate) fee_bump_percent: u64,
+    /// How many times a stuck transaction is replaced before giving up
+    #[arg(long, env = "ETHEREUM_MAX_REPLACEMENTS", default_value_t = 3)]
+    pub(crate) max_replacements: u32,
 }
 
 impl EthereumArgs {
@@ -43,7 +90,20 @@ impl EthereumArgs {
                 ws_url,
             )
         };
-        Ok(client)
+        Ok(client
+            .with_escrow_address(self.escrow_address)
+            .with_escrow_deployment_block(self.escrow_deployment_block)
+            .with_approval(self.approval)
+            .with_fee_config(EthFeeConfig {
+                mode: self.fee_mode,
+                max_fee_per_gas: self.max_fee_per_gas,
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+                gas_limit_multiplier: self.gas_limit_multiplier,
+                confirmations: self.confirmations,
+                timeout: Duration::from_secs(self.tx_timeout),
+                fee_bump_percent: self.fee_bump_percent,
+                max_replacements: self.max_replacements,
+            }))
     }
 
     pub fn build_signer(&self) -> Result<PrivateKeySigner, ChainError> {
@@ -55,4 +115,12 @@ impl EthereumArgs {
             PrivateKeySigner::decrypt_keystore(&self.keypair, "").map_err(Into::into)
         }
     }
-}
\ No newline at end of file
+}
+
+fn parse_gas_limit_multiplier(value: &str) -> Result<f64, String> {
+    let multiplier: f64 = value.parse().map_err(|e: std::num::ParseFloatError| e.to_string())?;
+    if multiplier.is_nan() || multiplier < 1.0 {
+        return Err(format!("{} would lower the gas limit below the estimate", multiplier));
+    }
+    Ok(multiplier)
+}
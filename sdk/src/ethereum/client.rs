@@ -1,16 +1,35 @@
+use crate::ethereum::events::{decode_event, spawn_event_stream, EventStreamConfig};
 use crate::ethereum::{ChainError, Network};
-use crate::Chain;
-use alloy::primitives::{Address, U256};
+use crate::events::{EscrowEvent, EscrowEventLog};
+use crate::transaction::{EscrowCall, TransactionStatus, TransactionSummary};
+use crate::{CancelOutcome, CancelReceipt, Chain, SettleReceipt, SimulationReport, SubmitReceipt};
+use alloy::primitives::{Address, TxHash, U256};
 use alloy::signers::local::PrivateKeySigner;
-use alloy::rpc::types::Transaction;
+use alloy::rpc::types::state::StateOverride;
+use alloy::rpc::types::{BlockId, BlockNumberOrTag, Filter, Log, TransactionRequest};
+use alloy::rpc::types::TransactionReceipt;
+use alloy::sol_types::{SolCall, SolEvent, SolInterface};
 use async_trait::async_trait;
-use mantis_common::UserIntent;
+use futures::Stream;
+use mantis_common::{Domain, Intent, IntentStatus, TokenAmount, UserIntent};
+use std::cmp::Ordering;
 use std::sync::Arc;
-use std::time::Duration;
-use alloy::providers::{ProviderBuilder, ReqwestProvider};
+use tokio::sync::OnceCell;
+use alloy::network::{Ethereum, EthereumWallet, TransactionBuilder};
+use alloy::providers::fillers::{
+    CachedNonceManager, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller, WalletFiller,
+};
+use alloy::providers::{Identity, Provider, ProviderBuilder, RootProvider};
 use alloy::sol;
+use alloy::transports::http::{Client, Http};
 use reqwest::Url;
-use crate::ethereum::client::Escrow::{EscrowInstance, IntentInfo};
+use crate::ethereum::client::Escrow::{
+    CrossChainMsgUser, EscrowCalls, EscrowInstance, FundsEscrowed, IntentInfo, SolverTransfer,
+};
+use crate::ethereum::erc20::{Approval, Erc20, Transfer};
+use crate::ethereum::fees::{send_transaction, EthFeeConfig};
+use crate::ethereum::logs::{block_ranges, get_logs_in, DEFAULT_MAX_BLOCK_RANGE};
+use crate::ethereum::revert::{revert_reason, EscrowRevert};
 
 sol!(
     #[sol(rpc)]
@@ -18,14 +37,42 @@ sol!(
     "../contracts/ethereum/abi/escrow.json"
 );
 
-type RpcProvider = ReqwestProvider;
+/// HTTP provider signing transactions with the client's keypair.
+///
+/// Nonces are cached so consecutive transactions don't wait on each other. The client's
+/// own transactions set their gas limit and fees according to its [`EthFeeConfig`].
+pub(super) type RpcProvider = FillProvider<
+    JoinFill<
+        JoinFill<JoinFill<JoinFill<Identity, GasFiller>, NonceFiller<CachedNonceManager>>, ChainIdFiller>,
+        WalletFiller<EthereumWallet>,
+    >,
+    RootProvider<Http<Client>>,
+    Http<Client>,
+    Ethereum,
+>;
 
-pub(crate) struct EthereumClient {
-    #[allow(unused)]
+/// An intent the signer won the auction of, to settle through [`EthereumClient::send_funds_to_user`].
+#[derive(Clone, Debug)]
+pub enum Settlement {
+    /// The intent is escrowed by this contract, which releases its input tokens to the solver.
+    SingleDomain { intent_id: String },
+    /// The intent is escrowed on another domain, read from there. Its escrow releases the
+    /// input tokens to `solver_out` once the bridge relays the payout.
+    CrossChain { intent: Intent, solver_out: String },
+}
+
+pub struct EthereumClient {
     rpc_client: RpcProvider,
     sender_keypair: Arc<PrivateKeySigner>,
-    #[allow(unused)]
+    ws_url: Url,
+    escrow_address: Option<Address>,
+    approval: Approval,
+    fee_config: EthFeeConfig,
     network: Network,
+    /// First block searched for the escrow's logs.
+    escrow_deployment_block: u64,
+    /// Set once the node's chain id was checked against `network`.
+    chain_id_verified: OnceCell<()>,
 }
 
 impl EthereumClient {
@@ -38,56 +85,1020 @@ impl EthereumClient {
         network: Network,
         sender_keypair: Arc<PrivateKeySigner>,
         rpc_url: Url,
-        _ws_url: Url,
+        ws_url: Url,
     ) -> Self {
-        let provider = ProviderBuilder::new().on_http(rpc_url);
-        // TODO: specify escrow address
+        let wallet = EthereumWallet::from(sender_keypair.as_ref().clone());
+        let provider = ProviderBuilder::new()
+            .with_gas_estimation()
+            .with_cached_nonce_management()
+            // transactions are signed for the configured network, see `verify_chain_id`
+            .with_chain_id(network.chain_id())
+            .wallet(wallet)
+            .on_http(rpc_url);
 
         Self {
             rpc_client: provider,
             sender_keypair,
+            ws_url,
+            escrow_address: None,
+            approval: Approval::default(),
+            fee_config: EthFeeConfig::default(),
             network,
+            escrow_deployment_block: 0,
+            chain_id_verified: OnceCell::new(),
+        }
+    }
+
+    /// Targets the escrow contract deployed at `escrow_address`. There's no default deployment,
+    /// escrow calls fail with [`ChainError::MissingEscrowAddress`] until one is set.
+    pub fn with_escrow_address(mut self, escrow_address: Address) -> Self {
+        self.escrow_address = Some(escrow_address);
+        self
+    }
+
+    /// Starts searches of the escrow's past logs at `block`, its deployment block, instead of
+    /// genesis. The search is chunked either way, this only saves requests.
+    pub fn with_escrow_deployment_block(mut self, block: u64) -> Self {
+        self.escrow_deployment_block = block;
+        self
+    }
+
+    /// Checks the RPC node serves the client's network.
+    ///
+    /// Transactions are signed for the network's chain id, so a node of another chain would
+    /// reject them anyway; this fails early with a clear error instead. Called before the
+    /// client sends transactions, and only queries the node once.
+    pub async fn verify_chain_id(&self) -> Result<(), ChainError> {
+        self.chain_id_verified
+            .get_or_try_init(|| async {
+                let actual = self
+                    .rpc_client
+                    .get_chain_id()
+                    .await
+                    .map_err(|e| ChainError::Other(format!("Failed to get chain id: {}", e)))?;
+                let expected = self.network.chain_id();
+                if actual != expected {
+                    return Err(ChainError::ChainIdMismatch {
+                        network: self.network,
+                        expected,
+                        actual,
+                    });
+                }
+                Ok(())
+            })
+            .await
+            .map(|_| ())
+    }
+
+    /// Sets how much to approve when the escrow's allowance is too low to escrow or
+    /// settle an intent, defaults to [`Approval::Exact`].
+    pub fn with_approval(mut self, approval: Approval) -> Self {
+        self.approval = approval;
+        self
+    }
+
+    /// Prices and waits for transactions according to `fee_config` instead of the defaults.
+    pub fn with_fee_config(mut self, fee_config: EthFeeConfig) -> Self {
+        self.fee_config = fee_config;
+        self
+    }
+
+    /// Returns the ERC-20 token at `address`, sending transactions with the client's fee config.
+    pub fn erc20(&self, address: Address) -> Erc20<RpcProvider> {
+        Erc20::new(address, self.rpc_client.clone()).with_fee_config(self.fee_config.clone())
+    }
+
+    /// Streams the escrow's `FundsEscrowed`, `CrossChainMsgSolver` and `CrossChainMsgUser` events.
+    ///
+    /// New blocks are picked up over the websocket endpoint, falling back to polling the
+    /// HTTP endpoint if it can't be reached or drops. Events are only yielded once their block
+    /// has `config.confirmations` confirmations, so they aren't reverted by shallow reorgs.
+    /// RPC failures are yielded as errors and retried on the next block; the stream ends
+    /// when it's dropped.
+    pub fn escrow_events(
+        &self,
+        config: EventStreamConfig,
+    ) -> Result<impl Stream<Item = Result<EscrowEventLog, ChainError>>, ChainError> {
+        let escrow = *self.escrow()?.address();
+        Ok(spawn_event_stream(
+            self.rpc_client.clone(),
+            self.ws_url.clone(),
+            escrow,
+            config,
+        ))
+    }
+
+    /// Settles an intent as its winning solver: pays `amount_out` of the intent's `token_out`
+    /// to its destination user through the escrow's `sendFundsToUser`.
+    ///
+    /// Approves the escrow to spend `amount_out` first if its allowance is lower, see
+    /// [`EthereumClient::with_approval`].
+    pub async fn send_funds_to_user(&self, settlement: Settlement) -> Result<SettleReceipt, ChainError> {
+        let (transfer, tx) = self.send_funds_to_user_transaction(settlement).await?;
+        self.erc20(transfer.tokenOut)
+            .ensure_allowance(self.signer(), *self.escrow()?.address(), transfer.amountOut, self.approval)
+            .await?;
+        let receipt = self.send_escrow_transaction(tx).await?;
+        Ok(SettleReceipt {
+            tx_hash: receipt.transaction_hash.to_string(),
+            block_number: receipt.block_number,
+        })
+    }
+
+    /// Simulates [`EthereumClient::send_funds_to_user`] without sending anything.
+    pub async fn simulate_send_funds_to_user(&self, settlement: Settlement) -> Result<SimulationReport, ChainError> {
+        let (transfer, tx) = self.send_funds_to_user_transaction(settlement).await?;
+        let approvals = self
+            .erc20(transfer.tokenOut)
+            .required_approvals(self.signer(), *self.escrow()?.address(), transfer.amountOut, self.approval)
+            .await?;
+        self.simulate(approvals, tx).await
+    }
+
+    /// Checks the signer may settle the intent and builds its `sendFundsToUser` transaction.
+    async fn send_funds_to_user_transaction(
+        &self,
+        settlement: Settlement,
+    ) -> Result<(SolverTransfer, TransactionRequest), ChainError> {
+        self.verify_chain_id().await?;
+        let (intent, single_domain, solver_out) = match settlement {
+            Settlement::SingleDomain { intent_id } => {
+                let mut intent = self
+                    .get_intent(&intent_id)
+                    .await?
+                    .ok_or(ChainError::IntentNotFound(intent_id))?;
+                // the escrow doesn't record the destination, settling single domain pays out here
+                intent.dst_domain = Some(intent.src_domain);
+                (intent, true, String::new())
+            }
+            Settlement::CrossChain { intent, solver_out } => (intent, false, solver_out),
+        };
+
+        let transfer = solver_transfer(
+            &intent,
+            single_domain,
+            solver_out,
+            self.signer(),
+            Domain::from(self.network),
+        )?;
+        let tx = self.escrow()?.sendFundsToUser(transfer.clone()).into_transaction_request();
+        Ok((transfer, tx))
+    }
+
+    /// Simulates `tx` with `eth_call`, even if `approvals` have to be sent first.
+    ///
+    /// The escrow's allowance is then overridden for the call, see [`Erc20::allowance_override`].
+    /// If that fails only the first approval is simulated, and the report says the escrow call
+    /// wasn't verified. Reverts are reported with their decoded reason, other RPC failures are
+    /// returned as errors.
+    async fn simulate(
+        &self,
+        approvals: Vec<TransactionRequest>,
+        tx: TransactionRequest,
+    ) -> Result<SimulationReport, ChainError> {
+        let tx = tx.with_from(self.signer());
+        let Some(approval) = approvals.into_iter().next() else {
+            let (success, error, gas_used) = self.simulate_call(&tx, None).await?;
+            return Ok(SimulationReport {
+                success,
+                error,
+                gas_used,
+                logs: Vec::new(),
+                approval: None,
+                escrow_call_verified: true,
+            });
+        };
+
+        let token = approval
+            .to
+            .and_then(|to| to.to().copied())
+            .ok_or_else(|| ChainError::Other("Approval has no token address".to_string()))?;
+        let overrides = self
+            .erc20(token)
+            .allowance_override(self.signer(), *self.escrow()?.address())
+            .await?;
+        let (simulated, escrow_call_verified) = match &overrides {
+            Some(_) => (tx, true),
+            None => (approval.with_from(self.signer()), false),
+        };
+        let (success, error, gas_used) = self.simulate_call(&simulated, overrides.as_ref()).await?;
+        Ok(SimulationReport {
+            success,
+            error,
+            gas_used,
+            logs: Vec::new(),
+            approval: Some(token.to_string()),
+            escrow_call_verified,
+        })
+    }
+
+    /// Runs `tx` with `eth_call` and returns whether it succeeded, its revert reason and its
+    /// estimated gas. Gas can't be estimated under `overrides`, so it's left out then.
+    async fn simulate_call(
+        &self,
+        tx: &TransactionRequest,
+        overrides: Option<&StateOverride>,
+    ) -> Result<(bool, Option<String>, Option<u64>), ChainError> {
+        let call = match overrides {
+            Some(overrides) => self.rpc_client.call(tx).overrides(overrides).await,
+            None => self.rpc_client.call(tx).await,
+        };
+        match call {
+            Ok(_) if overrides.is_some() => Ok((true, None, None)),
+            Ok(_) => {
+                let gas = self.rpc_client.estimate_gas(tx).await?;
+                Ok((true, None, Some(gas as u64)))
+            }
+            Err(err) => match revert_reason(&err) {
+                Some(reason) => Ok((false, Some(reason), None)),
+                None => Err(err.into()),
+            },
+        }
+    }
+
+    /// Checks the signer may cancel the intent and builds its `userCancelIntent` transaction.
+    /// The escrow doesn't store the destination domain, `dst_domain` is the one submitted.
+    async fn cancel_intent_transaction(
+        &self,
+        intent_id: &str,
+        dst_domain: Domain,
+    ) -> Result<TransactionRequest, ChainError> {
+        self.verify_chain_id().await?;
+        let info = self
+            .get_intent_info(intent_id.parse()?)
+            .await?
+            .ok_or_else(|| ChainError::IntentNotFound(intent_id.to_string()))?;
+        if info.srcUser != self.signer() {
+            return Err(ChainError::NotIntentUser {
+                intent_id: intent_id.to_string(),
+                src_user: info.srcUser.to_string(),
+            });
         }
+        let single_domain = dst_domain == Domain::from(self.network);
+        // a single domain intent pays out to an address on this chain
+        if single_domain && !info.dstUser.is_empty() && info.dstUser.parse::<Address>().is_err() {
+            return Err(ChainError::Other(format!(
+                "Intent {} pays out to {}, which isn't a user on {}",
+                intent_id, info.dstUser, dst_domain
+            )));
+        }
+        let timeout: u64 = info.timeout.saturating_to();
+        // the next block can't be older than the latest one
+        if timeout >= self.latest_block_timestamp().await? {
+            return Err(ChainError::IntentNotExpired {
+                intent_id: intent_id.to_string(),
+                timeout,
+            });
+        }
+        Ok(self
+            .escrow()?
+            .userCancelIntent(intent_id.parse()?, single_domain)
+            .into_transaction_request())
+    }
+
+    /// Checks `intent` can be escrowed by this client and converts it into the escrow's `IntentInfo`.
+    async fn intent_info(&self, intent: &UserIntent) -> Result<IntentInfo, ChainError> {
+        self.verify_chain_id().await?;
+        let domain = Domain::from(self.network);
+        if intent.src_domain != domain {
+            return Err(ChainError::DomainMismatch {
+                expected: domain,
+                actual: intent.src_domain,
+            });
+        }
+        IntentInfo::try_from(intent)
+    }
+
+    /// Sends a call to the escrow, decoding its revert reason if it fails a `require`.
+    async fn send_escrow_transaction(&self, tx: TransactionRequest) -> Result<TransactionReceipt, ChainError> {
+        send_transaction(&self.rpc_client, tx, &self.fee_config)
+            .await
+            .map_err(escrow_error)
+    }
+
+    fn escrow(&self) -> Result<EscrowInstance<Http<Client>, RpcProvider>, ChainError> {
+        let address = self.escrow_address.ok_or(ChainError::MissingEscrowAddress(self.network))?;
+        Ok(EscrowInstance::new(address, self.rpc_client.clone()))
+    }
+
+    async fn get_intent_info(&self, intent_id: U256) -> Result<Option<IntentInfo>, ChainError> {
+        let info = self.escrow()?.getIntentInfo(intent_id).call().await?._0;
+        // deleted or never created intents read back as zeroed structs
+        Ok((info.srcUser != Address::ZERO).then_some(info))
+    }
+
+    /// Returns the timestamp of the latest block, which the escrow checks timeouts against.
+    async fn latest_block_timestamp(&self) -> Result<u64, ChainError> {
+        let block = self
+            .rpc_client
+            .get_block_by_number(BlockNumberOrTag::Latest, false)
+            .await
+            .map_err(|e| ChainError::BlockNumberError(e.to_string()))?
+            .ok_or_else(|| ChainError::BlockNumberError("Latest block not found".to_string()))?;
+        Ok(block.header.timestamp)
+    }
+
+    /// Tells whether a deleted intent was settled or cancelled.
+    ///
+    /// The escrow deletes single domain intents in both cases without an event, but both
+    /// release the escrowed input. This finds the intent's deposit next to its `FundsEscrowed`
+    /// event, then the release of the same amount made by the transaction deleting the intent.
+    async fn closed_intent_status(&self, intent_id: U256) -> Result<IntentStatus, ChainError> {
+        let escrow = *self.escrow()?.address();
+        let escrowed = self.escrowed_log(intent_id).await?;
+        let (Some(tx_hash), Some(block_number), Some(log_index)) =
+            (escrowed.transaction_hash, escrowed.block_number, escrowed.log_index)
+        else {
+            return Err(ChainError::TransactionInfoError(format!(
+                "FundsEscrowed log of intent {} has no transaction",
+                intent_id
+            )));
+        };
+        let receipt = self
+            .rpc_client
+            .get_transaction_receipt(tx_hash)
+            .await
+            .map_err(|e| ChainError::TransactionInfoError(e.to_string()))?
+            .ok_or_else(|| {
+                ChainError::TransactionInfoError(format!("Receipt of {} not found", tx_hash))
+            })?;
+        // `escrowFunds` pulls the input with `transferFrom` right before emitting `FundsEscrowed`,
+        // which also holds when it's called through a contract
+        let (token_in, deposit) = receipt
+            .inner
+            .logs()
+            .iter()
+            .rev()
+            .filter(|log| log.log_index.is_some_and(|index| index < log_index))
+            .filter_map(|log| Some((log.address(), log.log_decode::<Transfer>().ok()?.inner.data)))
+            .find(|(_, transfer)| transfer.to == escrow)
+            .ok_or_else(|| {
+                ChainError::TransactionInfoError(format!(
+                    "Deposit of intent {} not found in {}",
+                    intent_id, tx_hash
+                ))
+            })?;
+
+        let releases = Filter::new()
+            .address(token_in)
+            .event_signature(Transfer::SIGNATURE_HASH)
+            .topic1(escrow.into_word());
+        let latest = self
+            .rpc_client
+            .get_block_number()
+            .await
+            .map_err(|e| ChainError::BlockNumberError(e.to_string()))?;
+        for (from, to) in block_ranges(block_number, latest, DEFAULT_MAX_BLOCK_RANGE) {
+            for log in get_logs_in(&self.rpc_client, &releases, from, to).await? {
+                let Ok(release) = log.log_decode::<Transfer>() else {
+                    continue;
+                };
+                let (Some(tx_hash), Some(block_number)) = (log.transaction_hash, log.block_number)
+                else {
+                    continue;
+                };
+                // settling and cancelling both release exactly the escrowed amount
+                if release.inner.data.value != deposit.value {
+                    continue;
+                }
+                let closed = self
+                    .closing_status(
+                        intent_id,
+                        tx_hash,
+                        block_number,
+                        deposit.from,
+                        release.inner.data.to,
+                    )
+                    .await?;
+                if let Some(status) = closed {
+                    return Ok(status);
+                }
+            }
+        }
+        Err(ChainError::TransactionInfoError(format!(
+            "No transaction deleting intent {} found",
+            intent_id
+        )))
+    }
+
+    /// Finds the `FundsEscrowed` log of `intent_id`, walking back from the latest block.
+    ///
+    /// The escrow numbers intents in order, so the walk stops at the first older intent.
+    async fn escrowed_log(&self, intent_id: U256) -> Result<Log, ChainError> {
+        let escrowed = Filter::new()
+            .address(*self.escrow()?.address())
+            .event_signature(FundsEscrowed::SIGNATURE_HASH);
+        let latest = self
+            .rpc_client
+            .get_block_number()
+            .await
+            .map_err(|e| ChainError::BlockNumberError(e.to_string()))?;
+        let ranges = block_ranges(self.escrow_deployment_block, latest, DEFAULT_MAX_BLOCK_RANGE);
+        for (from, to) in ranges.into_iter().rev() {
+            let logs = get_logs_in(&self.rpc_client, &escrowed, from, to).await?;
+            for log in logs.into_iter().rev() {
+                let Ok(event) = log.log_decode::<FundsEscrowed>() else {
+                    continue;
+                };
+                match event.inner.data.intentId.cmp(&intent_id) {
+                    Ordering::Equal => return Ok(log),
+                    Ordering::Less => return Err(ChainError::IntentNotFound(intent_id.to_string())),
+                    Ordering::Greater => {}
+                }
+            }
+        }
+        Err(ChainError::IntentNotFound(intent_id.to_string()))
+    }
+
+    /// Finds the transaction in which the signer already requested the cross-domain refund
+    /// of `intent_id`.
+    ///
+    /// The escrow keeps cross-domain intents until the bridge confirms the refund, and emits
+    /// `CrossChainMsgUser` again for every further `userCancelIntent`.
+    async fn cross_chain_cancel_request(&self, intent_id: U256) -> Result<Option<TxHash>, ChainError> {
+        let from_block = self
+            .escrowed_log(intent_id)
+            .await?
+            .block_number
+            .unwrap_or(self.escrow_deployment_block);
+        let requests = Filter::new()
+            .address(*self.escrow()?.address())
+            .event_signature(CrossChainMsgUser::SIGNATURE_HASH);
+        let latest = self
+            .rpc_client
+            .get_block_number()
+            .await
+            .map_err(|e| ChainError::BlockNumberError(e.to_string()))?;
+        for (from, to) in block_ranges(from_block, latest, DEFAULT_MAX_BLOCK_RANGE) {
+            for log in get_logs_in(&self.rpc_client, &requests, from, to).await? {
+                let Ok(event) = log.log_decode::<CrossChainMsgUser>() else {
+                    continue;
+                };
+                // anyone can emit the message for any intent, only the user's own request counts
+                let request = event.inner.data;
+                if request.intentId == intent_id && request.user == self.signer() {
+                    if let Some(tx_hash) = log.transaction_hash {
+                        return Ok(Some(tx_hash));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns how transaction `tx_hash`, which released an intent's input to `to`, closed
+    /// intent `intent_id`, or `None` if it closed another intent.
+    async fn closing_status(
+        &self,
+        intent_id: U256,
+        tx_hash: TxHash,
+        block_number: u64,
+        src_user: Address,
+        to: Address,
+    ) -> Result<Option<IntentStatus>, ChainError> {
+        let escrow = self.escrow()?;
+        let tx = self
+            .rpc_client
+            .get_transaction_by_hash(tx_hash)
+            .await
+            .map_err(|e| ChainError::TransactionInfoError(e.to_string()))?
+            .ok_or_else(|| {
+                ChainError::TransactionInfoError(format!("Transaction {} not found", tx_hash))
+            })?;
+        if tx.to == Some(*escrow.address()) {
+            return Ok(match EscrowCalls::abi_decode(&tx.input, false) {
+                Ok(EscrowCalls::userCancelIntent(call)) => {
+                    (call.intentId == intent_id).then_some(IntentStatus::Cancelled)
+                }
+                Ok(EscrowCalls::sendFundsToUser(call)) => {
+                    (call.solverTransferData.intentId == intent_id).then_some(IntentStatus::Settled)
+                }
+                _ => None,
+            });
+        }
+
+        // called through a contract such as a multisig, whose input can't be decoded here:
+        // check the intent was deleted in this block instead
+        let stored_at = |block: u64| {
+            let call = escrow.getIntentInfo(intent_id).block(BlockId::number(block));
+            async move { Ok::<_, ChainError>(call.call().await?._0.srcUser != Address::ZERO) }
+        };
+        if !stored_at(block_number.saturating_sub(1)).await? || stored_at(block_number).await? {
+            return Ok(None);
+        }
+        // only the source user can cancel and gets the refund, settlement pays the solver
+        Ok(Some(if to == src_user {
+            IntentStatus::Cancelled
+        } else {
+            IntentStatus::Settled
+        }))
+    }
+}
+
+/// Checks `signer` may settle `intent` on `domain` and builds the escrow's `sendFundsToUser`
+/// argument.
+fn solver_transfer(
+    intent: &Intent,
+    single_domain: bool,
+    solver_out: String,
+    signer: Address,
+    domain: Domain,
+) -> Result<SolverTransfer, ChainError> {
+    if intent.dst_domain != Some(domain) {
+        return Err(ChainError::DstDomainMismatch {
+            expected: domain,
+            actual: intent.dst_domain,
+        });
+    }
+    let winner_solver = intent
+        .winner_solver
+        .as_deref()
+        .ok_or_else(|| ChainError::AuctionPending(intent.intent_id.clone()))?;
+    if winner_solver.parse::<Address>().ok() != Some(signer) {
+        return Err(ChainError::NotWinnerSolver {
+            intent_id: intent.intent_id.clone(),
+            winner_solver: winner_solver.to_string(),
+        });
+    }
+    let token_out: Address = intent.token_out.parse().map_err(|_| ChainError::ParseAddressError)?;
+    let dst_user: Address = intent.dst_user.parse().map_err(|_| ChainError::ParseAddressError)?;
+
+    Ok(SolverTransfer {
+        intentId: escrow_intent_id(intent)?,
+        tokenOut: token_out,
+        amountOut: intent.amount_out.raw(),
+        dstUser: dst_user,
+        singleDomain: single_domain,
+        solverOut: solver_out,
+    })
+}
+
+/// Maps an intent's id to the `uint` the escrow identifies it with.
+///
+/// The escrow numbers its own intents, so ids of Ethereum intents are decimal numbers. Other
+/// domains derive 32 hex digit ids (see [`UserIntent::intent_id`]), which are read as a hex
+/// number. The escrow emits that number back in `CrossChainMsgSolver`, and the relayer formats
+/// it as 32 hex digits again to find the intent on its source domain.
+fn escrow_intent_id(intent: &Intent) -> Result<U256, ChainError> {
+    Ok(match intent.src_domain {
+        Domain::Ethereum(_) => intent.intent_id.parse()?,
+        _ => U256::from_str_radix(&intent.intent_id, 16)?,
+    })
+}
+
+/// Turns reverts of the escrow's `require`s into [`ChainError::EscrowReverted`].
+fn escrow_error(err: ChainError) -> ChainError {
+    let transport_error = match &err {
+        ChainError::RpcError(e) => e,
+        ChainError::ContractError(alloy::contract::Error::TransportError(e)) => e,
+        _ => return err,
+    };
+    match revert_reason(transport_error).and_then(|reason| EscrowRevert::from_reason(&reason)) {
+        Some(revert) => ChainError::EscrowReverted(revert),
+        None => err,
+    }
+}
+
+impl TryFrom<&UserIntent> for IntentInfo {
+    type Error = ChainError;
+
+    fn try_from(intent: &UserIntent) -> Result<Self, Self::Error> {
+        Ok(IntentInfo {
+            tokenIn: intent.token_in.parse().map_err(|_| ChainError::ParseAddressError)?,
+            amountIn: intent.amount_in.raw(),
+            srcUser: intent.src_user.parse().map_err(|_| ChainError::ParseAddressError)?,
+            tokenOut: intent.token_out.clone(),
+            amountOut: intent.amount_out.raw(),
+            dstUser: intent.dst_user.clone(),
+            winnerSolver: "".to_string(),
+            timeout: U256::from(intent.timeout),
+        })
+    }
+}
+
+/// Rebuilds the user intent stored as `info`, taking what the escrow doesn't store
+/// (domains and nonce) from `submitted`.
+fn user_intent_from_info(info: &IntentInfo, submitted: &UserIntent) -> UserIntent {
+    // decimals aren't stored by the escrow, they don't affect the intent id either
+    UserIntent {
+        src_domain: submitted.src_domain,
+        dst_domain: submitted.dst_domain,
+        src_user: info.srcUser.to_string(),
+        dst_user: info.dstUser.clone(),
+        token_in: info.tokenIn.to_string(),
+        amount_in: TokenAmount::new(info.amountIn, 0),
+        token_out: info.tokenOut.clone(),
+        amount_out: TokenAmount::new(info.amountOut, 0),
+        timeout: info.timeout.saturating_to(),
+        nonce: submitted.nonce,
     }
 }
 
+/// Decodes a call to the escrow, ignoring admin and bridge entry points.
+fn decode_call(input: &[u8]) -> Option<EscrowCall> {
+    let call = match EscrowCalls::abi_decode(input, false).ok()? {
+        EscrowCalls::escrowFunds(call) => {
+            let info = call.newIntentInfo;
+            EscrowCall::EscrowFunds {
+                // assigned by the escrow, see `FundsEscrowed`
+                intent_id: None,
+                user: info.srcUser.to_string(),
+                token_in: info.tokenIn.to_string(),
+                amount_in: info.amountIn.to_string(),
+                token_out: info.tokenOut,
+                amount_out: info.amountOut.to_string(),
+            }
+        }
+        EscrowCalls::updateAuctionData(call) => EscrowCall::UpdateAuctionData {
+            intent_id: call.intentId.to_string(),
+            amount_out: call.amountOut.to_string(),
+            winner_solver: call.winnerSolver,
+        },
+        EscrowCalls::sendFundsToUser(call) => EscrowCall::SendFundsToUser {
+            intent_id: Some(call.solverTransferData.intentId.to_string()),
+        },
+        EscrowCalls::userCancelIntent(call) => EscrowCall::UserCancelIntent {
+            intent_id: Some(call.intentId.to_string()),
+        },
+        _ => return None,
+    };
+    Some(call)
+}
+
 #[async_trait]
 impl Chain for EthereumClient {
-    type Transaction = Transaction;
+    type Transaction = TransactionSummary;
     type Address = Address;
     type Token = String;
     type Amount = U256;
     type Error = ChainError;
 
-    async fn get_transaction(&self, _tx_hash: &str) -> Result<Self::Transaction, Self::Error> {
-        todo!()
+    async fn get_transaction(&self, tx_hash: &str) -> Result<Self::Transaction, Self::Error> {
+        let hash: TxHash = tx_hash.parse().map_err(|e| {
+            ChainError::TransactionInfoError(format!("Invalid transaction hash {}: {}", tx_hash, e))
+        })?;
+        let tx = self
+            .rpc_client
+            .get_transaction_by_hash(hash)
+            .await
+            .map_err(|e| ChainError::TransactionInfoError(e.to_string()))?
+            .ok_or_else(|| ChainError::TransactionInfoError(format!("Transaction {} not found", tx_hash)))?;
+        let receipt = self
+            .rpc_client
+            .get_transaction_receipt(hash)
+            .await
+            .map_err(|e| ChainError::TransactionInfoError(e.to_string()))?;
+
+        let calls = match (tx.to, self.escrow_address) {
+            (Some(to), Some(escrow)) if to == escrow => decode_call(&tx.input).into_iter().collect(),
+            _ => Vec::new(),
+        };
+
+        // no receipt means the transaction is still in the mempool
+        let Some(receipt) = receipt else {
+            return Ok(TransactionSummary {
+                tx_hash: hash.to_string(),
+                status: TransactionStatus::Pending,
+                fee: None,
+                block_number: None,
+                calls,
+                events: Vec::new(),
+            });
+        };
+
+        let events = receipt
+            .inner
+            .logs()
+            .iter()
+            .filter(|log| Some(log.address()) == self.escrow_address)
+            .filter_map(decode_event)
+            .collect();
+
+        Ok(TransactionSummary {
+            tx_hash: hash.to_string(),
+            status: if receipt.status() {
+                TransactionStatus::Success
+            } else {
+                TransactionStatus::Failed
+            },
+            fee: Some(receipt.gas_used * receipt.effective_gas_price),
+            block_number: receipt.block_number,
+            calls,
+            events,
+        })
+    }
+
+    async fn get_current_block_number(&self) -> Result<u64, Self::Error> {
+        self.rpc_client
+            .get_block_number()
+            .await
+            .map_err(|e| ChainError::BlockNumberError(e.to_string()))
+    }
+
+    async fn token_decimals(&self, token: &str) -> Result<u8, Self::Error> {
+        let address: Address = token.parse().map_err(|_| ChainError::ParseAddressError)?;
+        self.erc20(address).decimals().await
     }
 
     async fn submit_intent(
         &self,
         intent: UserIntent,
-        _contract_address: Self::Address,
-    ) -> Result<(), Self::Error> {
-        let intent_id = "123".to_string(); // TODO
-        let escrow = EscrowInstance::new(Address::default(), self.rpc_client.clone());
-        let method = escrow.escrowFunds(
-            intent_id,
-            IntentInfo {
-                tokenIn: intent.token_in.parse().map_err(|_| ChainError::ParseAddressError)?,
-                amountIn: intent.amount_in.parse()?,
-                srcUser: intent.user_address.parse().map_err(|_| ChainError::ParseAddressError)?,
-                tokenOut: intent.token_out,
-                amountOut: intent.amount_out,
-                dstUser: "".to_string(),
-                winnerSolver: "".to_string(),
-                timeout: Default::default(),
+    ) -> Result<SubmitReceipt, Self::Error> {
+        let info = self.intent_info(&intent).await?;
+        // derive from the canonical form, which is what can be read back from the escrow
+        let expected_id = user_intent_from_info(&info, &intent).intent_id();
+
+        let escrow = self.escrow()?;
+        // `escrowFunds` pulls the input tokens with `transferFrom`
+        self.erc20(info.tokenIn)
+            .ensure_allowance(self.signer(), *escrow.address(), info.amountIn, self.approval)
+            .await?;
+
+        let tx = escrow.escrowFunds(info).into_transaction_request();
+        let receipt = self.send_escrow_transaction(tx).await?;
+
+        // the escrow assigns intent ids itself and reports them in `FundsEscrowed`
+        let intent_id = receipt
+            .inner
+            .logs()
+            .iter()
+            .filter(|log| log.address() == *escrow.address())
+            .find_map(|log| log.log_decode::<FundsEscrowed>().ok())
+            .map(|log| log.inner.data.intentId)
+            .ok_or_else(|| {
+                ChainError::StoreIntentError(format!(
+                    "FundsEscrowed event not found in transaction {}",
+                    receipt.transaction_hash
+                ))
+            })?;
+
+        // the counter id is only known once mined, make sure it points at our intent
+        let stored = self
+            .get_intent_info(intent_id)
+            .await?
+            .ok_or_else(|| ChainError::IntentNotFound(intent_id.to_string()))?;
+        if user_intent_from_info(&stored, &intent).intent_id() != expected_id {
+            return Err(ChainError::StoreIntentError(format!(
+                "Escrowed intent {} doesn't match the submitted intent",
+                intent_id
+            )));
+        }
+
+        Ok(SubmitReceipt {
+            tx_hash: receipt.transaction_hash.to_string(),
+            block_number: receipt.block_number,
+            intent_id: intent_id.to_string(),
+        })
+    }
+
+    async fn simulate_submit_intent(&self, intent: UserIntent) -> Result<SimulationReport, Self::Error> {
+        let info = self.intent_info(&intent).await?;
+        let escrow = self.escrow()?;
+        let approvals = self
+            .erc20(info.tokenIn)
+            .required_approvals(self.signer(), *escrow.address(), info.amountIn, self.approval)
+            .await?;
+        let tx = escrow.escrowFunds(info).into_transaction_request();
+        self.simulate(approvals, tx).await
+    }
+
+    async fn get_intent(&self, intent_id: &str) -> Result<Option<Intent>, Self::Error> {
+        let Some(info) = self.get_intent_info(intent_id.parse()?).await? else {
+            return Ok(None);
+        };
+        let decimals_in = self.token_decimals(&info.tokenIn.to_string()).await?;
+        // the decimals of tokens on other chains aren't known here, keep those in base units
+        let decimals_out = match info.tokenOut.parse::<Address>() {
+            Ok(token_out) => self.token_decimals(&token_out.to_string()).await?,
+            Err(_) => 0,
+        };
+        let dst_user = if info.dstUser.is_empty() {
+            info.srcUser.to_string()
+        } else {
+            info.dstUser
+        };
+        Ok(Some(Intent {
+            intent_id: intent_id.to_string(),
+            src_domain: Domain::from(self.network),
+            // the escrow doesn't store the destination domain
+            dst_domain: None,
+            src_user: info.srcUser.to_string(),
+            dst_user,
+            token_in: info.tokenIn.to_string(),
+            amount_in: TokenAmount::new(info.amountIn, decimals_in),
+            token_out: info.tokenOut,
+            amount_out: TokenAmount::new(info.amountOut, decimals_out),
+            winner_solver: (!info.winnerSolver.is_empty()).then_some(info.winnerSolver),
+            timeout: info.timeout.saturating_to(),
+        }))
+    }
+
+    async fn intent_status(&self, intent_id: &str) -> Result<IntentStatus, Self::Error> {
+        match self.get_intent(intent_id).await? {
+            Some(intent) => Ok(intent.status(self.latest_block_timestamp().await?)),
+            None => self.closed_intent_status(intent_id.parse()?).await,
+        }
+    }
+
+    async fn cancel_intent(&self, intent_id: &str, dst_domain: Domain) -> Result<CancelReceipt, Self::Error> {
+        let tx = self.cancel_intent_transaction(intent_id, dst_domain).await?;
+        if dst_domain != Domain::from(self.network) {
+            // cancelling again would pay for a second, duplicate bridge message
+            if let Some(tx_hash) = self.cross_chain_cancel_request(intent_id.parse()?).await? {
+                return Ok(CancelReceipt {
+                    tx_hash: tx_hash.to_string(),
+                    outcome: CancelOutcome::AlreadyRequested,
+                });
             }
-        );
-        let _tx_hash = method.send().await?.with_timeout(Some(Duration::from_secs(30))).watch().await?;
-        Ok(())
+        }
+        let receipt = self.send_escrow_transaction(tx).await?;
+        if !receipt.status() {
+            return Err(ChainError::TransactionProcessingError(format!(
+                "Cancel transaction {} reverted",
+                receipt.transaction_hash
+            )));
+        }
+        let bridged = receipt
+            .inner
+            .logs()
+            .iter()
+            .filter_map(decode_event)
+            .any(|event| matches!(event, EscrowEvent::CrossChainMsgUser { .. }));
+
+        Ok(CancelReceipt {
+            tx_hash: receipt.transaction_hash.to_string(),
+            outcome: if bridged {
+                CancelOutcome::CrossChainMsgUser
+            } else {
+                CancelOutcome::Refunded
+            },
+        })
+    }
+
+    async fn simulate_cancel_intent(
+        &self,
+        intent_id: &str,
+        dst_domain: Domain,
+    ) -> Result<SimulationReport, Self::Error> {
+        let tx = self.cancel_intent_transaction(intent_id, dst_domain).await?;
+        self.simulate(Vec::new(), tx).await
+    }
+
+    async fn settle_intent(&self, intent_id: &str) -> Result<SettleReceipt, Self::Error> {
+        let settlement = Settlement::SingleDomain {
+            intent_id: intent_id.to_string(),
+        };
+        self.send_funds_to_user(settlement).await
+    }
+
+    async fn simulate_settle_intent(&self, intent_id: &str) -> Result<SimulationReport, Self::Error> {
+        let settlement = Settlement::SingleDomain {
+            intent_id: intent_id.to_string(),
+        };
+        self.simulate_send_funds_to_user(settlement).await
     }
 
     fn signer(&self) -> Self::Address {
         self.sender_keypair.address()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mantis_common::{EthereumNetwork, SolanaCluster};
+
+    fn user_intent() -> UserIntent {
+        UserIntent {
+            src_domain: Domain::Ethereum(EthereumNetwork::Mainnet),
+            dst_domain: Domain::Solana(SolanaCluster::Mainnet),
+            src_user: "0x39f98f32eb5fe4c568c7252e45fd48f8dc415d8e".to_string(),
+            dst_user: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+            token_in: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+            amount_in: TokenAmount::new(U256::from(1_500_000), 6),
+            token_out: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            amount_out: TokenAmount::new(U256::from(1_490_000), 6),
+            timeout: 1_700_000_000,
+            nonce: 7,
+        }
+    }
+
+    #[test]
+    fn converts_user_intent_into_intent_info() {
+        let intent = user_intent();
+        let info = IntentInfo::try_from(&intent).unwrap();
+        assert_eq!(info.srcUser, intent.src_user.parse::<Address>().unwrap());
+        assert_eq!(info.dstUser, intent.dst_user);
+        assert_eq!(info.tokenIn, intent.token_in.parse::<Address>().unwrap());
+        assert_eq!(info.amountIn, U256::from(1_500_000));
+        assert_eq!(info.tokenOut, intent.token_out);
+        assert_eq!(info.amountOut, U256::from(1_490_000));
+        assert!(info.winnerSolver.is_empty());
+        assert_eq!(info.timeout, U256::from(intent.timeout));
+    }
+
+    #[test]
+    fn intent_info_round_trips_to_a_canonical_user_intent() {
+        let intent = user_intent();
+        let info = IntentInfo::try_from(&intent).unwrap();
+        let canonical = user_intent_from_info(&info, &intent);
+        // addresses come back checksummed, everything the escrow stores is kept
+        assert_eq!(canonical.src_user, "0x39F98f32eb5fe4C568c7252e45fd48f8DC415d8e");
+        assert_eq!(canonical.token_in, "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        assert_eq!(canonical.src_domain, intent.src_domain);
+        assert_eq!(canonical.dst_domain, intent.dst_domain);
+        assert_eq!(canonical.dst_user, intent.dst_user);
+        assert_eq!(canonical.token_out, intent.token_out);
+        assert_eq!(canonical.amount_in.raw(), intent.amount_in.raw());
+        assert_eq!(canonical.amount_out.raw(), intent.amount_out.raw());
+        assert_eq!(canonical.timeout, intent.timeout);
+        assert_eq!(canonical.nonce, intent.nonce);
+
+        // the canonical form is a fixed point, so its id matches what's read back from the escrow
+        let info = IntentInfo::try_from(&canonical).unwrap();
+        assert_eq!(user_intent_from_info(&info, &canonical).intent_id(), canonical.intent_id());
+    }
+
+    fn solana_intent() -> Intent {
+        Intent {
+            intent_id: "0123456789abcdef0123456789abcdef".to_string(),
+            src_domain: Domain::Solana(SolanaCluster::Mainnet),
+            dst_domain: Some(Domain::Ethereum(EthereumNetwork::Mainnet)),
+            src_user: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+            dst_user: "0x39f98f32eb5fe4c568c7252e45fd48f8dc415d8e".to_string(),
+            token_in: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            amount_in: TokenAmount::new(U256::from(1_500_000), 6),
+            token_out: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+            amount_out: TokenAmount::new(U256::from(1_490_000), 6),
+            winner_solver: Some(solver().to_string()),
+            timeout: 1_700_000_000,
+        }
+    }
+
+    fn solver() -> Address {
+        Address::repeat_byte(0x11)
+    }
+
+    #[test]
+    fn maps_intent_ids_to_escrow_uints() {
+        let mut intent = solana_intent();
+        assert_eq!(
+            escrow_intent_id(&intent).unwrap(),
+            U256::from(0x0123456789abcdef0123456789abcdef_u128)
+        );
+        // hex ids without letters aren't read as decimal
+        intent.intent_id = "00000000000000000000000000000010".to_string();
+        assert_eq!(escrow_intent_id(&intent).unwrap(), U256::from(16));
+        intent.intent_id = "not hex".to_string();
+        assert!(matches!(escrow_intent_id(&intent), Err(ChainError::ParseUintError(_))));
+
+        intent.src_domain = Domain::Ethereum(EthereumNetwork::Mainnet);
+        intent.intent_id = "10".to_string();
+        assert_eq!(escrow_intent_id(&intent).unwrap(), U256::from(10));
+    }
+
+    #[test]
+    fn builds_cross_chain_solver_transfer() {
+        let intent = solana_intent();
+        let domain = Domain::Ethereum(EthereumNetwork::Mainnet);
+        let transfer = solver_transfer(&intent, false, "solver-out".to_string(), solver(), domain).unwrap();
+        assert_eq!(transfer.intentId, U256::from(0x0123456789abcdef0123456789abcdef_u128));
+        assert_eq!(transfer.tokenOut, intent.token_out.parse::<Address>().unwrap());
+        assert_eq!(transfer.amountOut, U256::from(1_490_000));
+        assert_eq!(transfer.dstUser, intent.dst_user.parse::<Address>().unwrap());
+        assert!(!transfer.singleDomain);
+        assert_eq!(transfer.solverOut, "solver-out");
+    }
+
+    #[test]
+    fn solver_transfer_rejects_other_destinations_and_solvers() {
+        let intent = solana_intent();
+        let sepolia = Domain::Ethereum(EthereumNetwork::Sepolia);
+        assert!(matches!(
+            solver_transfer(&intent, false, String::new(), solver(), sepolia),
+            Err(ChainError::DstDomainMismatch { .. })
+        ));
+
+        let mainnet = Domain::Ethereum(EthereumNetwork::Mainnet);
+        assert!(matches!(
+            solver_transfer(&intent, false, String::new(), Address::repeat_byte(0x22), mainnet),
+            Err(ChainError::NotWinnerSolver { .. })
+        ));
+        let pending = Intent { winner_solver: None, ..solana_intent() };
+        assert!(matches!(
+            solver_transfer(&pending, false, String::new(), solver(), mainnet),
+            Err(ChainError::AuctionPending(_))
+        ));
+    }
+
+    #[test]
+    fn intent_info_requires_ethereum_source_addresses() {
+        let mut intent = user_intent();
+        intent.src_user = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string();
+        assert!(matches!(IntentInfo::try_from(&intent), Err(ChainError::ParseAddressError)));
+
+        let mut intent = user_intent();
+        intent.token_in = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string();
+        assert!(matches!(IntentInfo::try_from(&intent), Err(ChainError::ParseAddressError)));
+    }
+}
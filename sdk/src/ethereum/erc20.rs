@@ -0,0 +1,365 @@
+use crate::ethereum::fees::{send_transaction, EthFeeConfig};
+use crate::ethereum::ChainError;
+use alloy::primitives::{Address, TxHash, B256, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::state::{AccountOverride, StateOverride};
+use alloy::rpc::types::TransactionRequest;
+use alloy::signers::local::PrivateKeySigner;
+use alloy::signers::SignerSync;
+use alloy::sol;
+use alloy::sol_types::{Eip712Domain, SolStruct};
+use alloy::transports::http::{Client, Http};
+use clap::ValueEnum;
+
+sol! {
+    #[sol(rpc)]
+    interface IERC20 {
+        event Transfer(address indexed from, address indexed to, uint256 value);
+
+        function name() external view returns (string);
+        function decimals() external view returns (uint8);
+        function balanceOf(address owner) external view returns (uint256);
+        function allowance(address owner, address spender) external view returns (uint256);
+        function approve(address spender, uint256 value) external returns (bool);
+
+        // EIP-2612
+        function nonces(address owner) external view returns (uint256);
+        function DOMAIN_SEPARATOR() external view returns (bytes32);
+        function permit(
+            address owner,
+            address spender,
+            uint256 value,
+            uint256 deadline,
+            uint8 v,
+            bytes32 r,
+            bytes32 s
+        ) external;
+
+        // EIP-5267
+        function eip712Domain() external view returns (
+            bytes1 fields,
+            string name,
+            string version,
+            uint256 chainId,
+            address verifyingContract,
+            bytes32 salt,
+            uint256[] extensions
+        );
+    }
+
+    /// EIP-2612 permit message.
+    struct Permit {
+        address owner;
+        address spender;
+        uint256 value;
+        uint256 nonce;
+        uint256 deadline;
+    }
+}
+
+pub use IERC20::Transfer;
+
+/// How much to approve when a spender's allowance is too low.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum Approval {
+    /// Approve exactly the amount about to be spent.
+    #[default]
+    Exact,
+    /// Approve `U256::MAX`, so later transfers don't need another approval.
+    Unlimited,
+}
+
+impl Approval {
+    pub fn amount(&self, required: U256) -> U256 {
+        match self {
+            Approval::Exact => required,
+            Approval::Unlimited => U256::MAX,
+        }
+    }
+
+    /// Returns the amounts to approve, in order, to raise an allowance of `current` to at
+    /// least `required`.
+    ///
+    /// Some tokens, USDT among them, revert when an allowance is changed from one nonzero
+    /// value to another, so a nonzero allowance is reset to 0 first.
+    pub fn amounts(&self, current: U256, required: U256) -> Vec<U256> {
+        if current >= required {
+            Vec::new()
+        } else if current.is_zero() {
+            vec![self.amount(required)]
+        } else {
+            vec![U256::ZERO, self.amount(required)]
+        }
+    }
+}
+
+/// An ERC-20 token contract.
+pub struct Erc20<P> {
+    instance: IERC20::IERC20Instance<Http<Client>, P>,
+    fee_config: EthFeeConfig,
+}
+
+impl<P: Provider<Http<Client>>> Erc20<P> {
+    pub fn new(address: Address, provider: P) -> Self {
+        Self {
+            instance: IERC20::new(address, provider),
+            fee_config: EthFeeConfig::default(),
+        }
+    }
+
+    /// Prices and waits for the token's transactions according to `fee_config`.
+    pub fn with_fee_config(mut self, fee_config: EthFeeConfig) -> Self {
+        self.fee_config = fee_config;
+        self
+    }
+
+    pub fn address(&self) -> Address {
+        *self.instance.address()
+    }
+
+    pub async fn decimals(&self) -> Result<u8, ChainError> {
+        Ok(self.instance.decimals().call().await?._0)
+    }
+
+    pub async fn balance_of(&self, owner: Address) -> Result<U256, ChainError> {
+        Ok(self.instance.balanceOf(owner).call().await?._0)
+    }
+
+    pub async fn allowance(&self, owner: Address, spender: Address) -> Result<U256, ChainError> {
+        Ok(self.instance.allowance(owner, spender).call().await?._0)
+    }
+
+    /// Approves `spender` to transfer `amount` of the sender's tokens.
+    pub async fn approve(&self, spender: Address, amount: U256) -> Result<TxHash, ChainError> {
+        let tx = self.instance.approve(spender, amount).into_transaction_request();
+        let receipt = send_transaction(self.instance.provider(), tx, &self.fee_config).await?;
+        Ok(receipt.transaction_hash)
+    }
+
+    /// Makes sure `spender` may transfer `required` of `owner`'s tokens, approving it
+    /// according to `approval` if not.
+    ///
+    /// Fails if `owner` holds less than `required`, since spending would revert anyway.
+    /// Returns the approval transactions, none if the allowance was high enough, see
+    /// [`Approval::amounts`].
+    pub async fn ensure_allowance(
+        &self,
+        owner: Address,
+        spender: Address,
+        required: U256,
+        approval: Approval,
+    ) -> Result<Vec<TxHash>, ChainError> {
+        let mut hashes = Vec::new();
+        for tx in self.required_approvals(owner, spender, required, approval).await? {
+            let receipt = send_transaction(self.instance.provider(), tx, &self.fee_config).await?;
+            hashes.push(receipt.transaction_hash);
+        }
+        Ok(hashes)
+    }
+
+    /// Returns the `approve` transactions [`Erc20::ensure_allowance`] would send, in order,
+    /// without sending them.
+    pub async fn required_approvals(
+        &self,
+        owner: Address,
+        spender: Address,
+        required: U256,
+        approval: Approval,
+    ) -> Result<Vec<TransactionRequest>, ChainError> {
+        let balance = self.balance_of(owner).await?;
+        if balance < required {
+            return Err(ChainError::InsufficientBalance {
+                token: self.address(),
+                balance,
+                required,
+            });
+        }
+        let current = self.allowance(owner, spender).await?;
+        Ok(approval
+            .amounts(current, required)
+            .into_iter()
+            .map(|amount| self.instance.approve(spender, amount).into_transaction_request())
+            .collect())
+    }
+
+    /// Returns a state override raising `spender`'s allowance of `owner`'s tokens to
+    /// `U256::MAX`, to simulate calls that depend on an approval which isn't sent yet.
+    ///
+    /// The allowance's storage slot depends on the token's layout, so it's found among the
+    /// slots `allowance` reads. `None` if the node doesn't support access lists or state
+    /// overrides, or no slot holds the allowance.
+    pub async fn allowance_override(
+        &self,
+        owner: Address,
+        spender: Address,
+    ) -> Result<Option<StateOverride>, ChainError> {
+        let tx = self.instance.allowance(owner, spender).into_transaction_request();
+        let Ok(access_list) = self.instance.provider().create_access_list(&tx).await else {
+            return Ok(None);
+        };
+        let slots = access_list
+            .access_list
+            .0
+            .into_iter()
+            .filter(|item| item.address == self.address())
+            .flat_map(|item| item.storage_keys);
+        for slot in slots {
+            let mut overrides = StateOverride::default();
+            overrides.insert(
+                self.address(),
+                AccountOverride {
+                    state_diff: Some([(slot, B256::from(U256::MAX))].into_iter().collect()),
+                    ..Default::default()
+                },
+            );
+            let allowance = self.instance.allowance(owner, spender).state(overrides.clone()).call().await;
+            if allowance.is_ok_and(|allowance| allowance._0 == U256::MAX) {
+                return Ok(Some(overrides));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Approves `spender` through an EIP-2612 `permit` signed by `owner`.
+    ///
+    /// The permit can be submitted by any account, e.g. a relayer paying the gas.
+    /// Fails with [`ChainError::PermitUnsupported`] if the token doesn't implement EIP-2612.
+    pub async fn permit(
+        &self,
+        owner: &PrivateKeySigner,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+    ) -> Result<TxHash, ChainError> {
+        let unsupported = || ChainError::PermitUnsupported(self.address());
+        let domain_separator = self
+            .instance
+            .DOMAIN_SEPARATOR()
+            .call()
+            .await
+            .map_err(|_| unsupported())?
+            ._0;
+        let nonce = self
+            .instance
+            .nonces(owner.address())
+            .call()
+            .await
+            .map_err(|_| unsupported())?
+            ._0;
+
+        let domain = self.eip712_domain().await?;
+        // a mismatch means the token uses a domain this can't reproduce
+        if domain.separator() != domain_separator {
+            return Err(unsupported());
+        }
+        let permit = Permit {
+            owner: owner.address(),
+            spender,
+            value,
+            nonce,
+            deadline,
+        };
+        let signature = owner
+            .sign_hash_sync(&permit.eip712_signing_hash(&domain))
+            .map_err(|e| ChainError::Other(format!("Failed to sign permit: {}", e)))?;
+
+        let tx = self.instance.permit(
+            owner.address(),
+            spender,
+            value,
+            deadline,
+            27 + signature.v().y_parity_byte(),
+            B256::from(signature.r()),
+            B256::from(signature.s()),
+        );
+        let receipt = send_transaction(self.instance.provider(), tx.into_transaction_request(), &self.fee_config).await?;
+        Ok(receipt.transaction_hash)
+    }
+
+    /// Returns the token's EIP-712 domain, read through EIP-5267 if implemented and
+    /// assuming OpenZeppelin's version `"1"` otherwise.
+    async fn eip712_domain(&self) -> Result<Eip712Domain, ChainError> {
+        if let Ok(domain) = self.instance.eip712Domain().call().await {
+            return Ok(token_domain(
+                domain.name,
+                domain.version,
+                domain.chainId,
+                domain.verifyingContract,
+            ));
+        }
+        let name = self.instance.name().call().await?._0;
+        let chain_id = self
+            .instance
+            .provider()
+            .get_chain_id()
+            .await
+            .map_err(|e| ChainError::Other(format!("Failed to get chain id: {}", e)))?;
+        Ok(token_domain(name, "1".to_string(), U256::from(chain_id), self.address()))
+    }
+}
+
+fn token_domain(name: String, version: String, chain_id: U256, token: Address) -> Eip712Domain {
+    Eip712Domain::new(Some(name.into()), Some(version.into()), Some(chain_id), Some(token), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{address, b256};
+
+    #[test]
+    fn approval_amount() {
+        let required = U256::from(1_000);
+        assert_eq!(Approval::Exact.amount(required), required);
+        assert_eq!(Approval::Unlimited.amount(required), U256::MAX);
+        assert_eq!(Approval::default(), Approval::Exact);
+    }
+
+    #[test]
+    fn approval_amounts_reset_nonzero_allowances() {
+        let required = U256::from(1_000);
+        assert!(Approval::Exact.amounts(required, required).is_empty());
+        assert!(Approval::Unlimited.amounts(U256::MAX, required).is_empty());
+        assert_eq!(Approval::Exact.amounts(U256::ZERO, required), vec![required]);
+        assert_eq!(Approval::Unlimited.amounts(U256::ZERO, required), vec![U256::MAX]);
+        assert_eq!(
+            Approval::Exact.amounts(U256::from(999), required),
+            vec![U256::ZERO, required]
+        );
+        assert_eq!(
+            Approval::Unlimited.amounts(U256::from(1), required),
+            vec![U256::ZERO, U256::MAX]
+        );
+    }
+
+    #[test]
+    fn permit_digest_matches_known_vector() {
+        // mainnet USDC, whose DOMAIN_SEPARATOR() returns this separator
+        let domain = token_domain(
+            "USD Coin".to_string(),
+            "2".to_string(),
+            U256::from(1),
+            address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+        );
+        assert_eq!(
+            domain.separator(),
+            b256!("06c37168a7db5138defc7866392bb87a741f9b3d104deb5094588ce041cae335")
+        );
+        let permit = Permit {
+            owner: address!("39F98f32eb5fe4C568c7252e45fd48f8DC415d8e"),
+            spender: address!("1111111111111111111111111111111111111111"),
+            value: U256::from(1_000_000),
+            nonce: U256::ZERO,
+            deadline: U256::from(1_700_000_000),
+        };
+        assert_eq!(
+            permit.eip712_type_hash(),
+            b256!("6e71edae12b1b97f4d1f60370fef10105fa2faae0126114a169c64845d6126c9")
+        );
+        assert_eq!(
+            permit.eip712_signing_hash(&domain),
+            b256!("ab2665f0b80fce8ab4593dc12561aa96fbcf43e48196c4b5165461b9d60d4c06")
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use crate::ethereum::client::Escrow::{CrossChainMsgSolver, CrossChainMsgUser, FundsEscrowed};
+use crate::ethereum::client::RpcProvider;
+use crate::ethereum::logs::{block_ranges, get_logs_in, DEFAULT_MAX_BLOCK_RANGE};
+use crate::ethereum::ChainError;
+use crate::events::{EscrowEvent, EscrowEventLog};
+use alloy::primitives::Address;
+use alloy::providers::{Provider, ProviderBuilder, WsConnect};
+use alloy::rpc::types::{Filter, Log};
+use alloy::sol_types::SolEvent;
+use futures::{Stream, StreamExt};
+use reqwest::Url;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Options of [`EthereumClient::escrow_events`](crate::ethereum::EthereumClient::escrow_events).
+#[derive(Clone, Debug)]
+pub struct EventStreamConfig {
+    /// Blocks that must be built on top of an event's block before it's yielded.
+    pub confirmations: u64,
+    /// How often to check for new blocks when the websocket endpoint isn't available.
+    pub poll_interval: Duration,
+    /// First block to yield events from, defaults to the first block confirmed after subscribing.
+    pub from_block: Option<u64>,
+    /// Blocks fetched per `eth_getLogs` call, so catching up from an old `from_block` stays
+    /// within the range RPC nodes serve.
+    pub max_block_range: u64,
+}
+
+impl Default for EventStreamConfig {
+    fn default() -> Self {
+        Self {
+            confirmations: 12,
+            poll_interval: Duration::from_secs(12),
+            from_block: None,
+            max_block_range: DEFAULT_MAX_BLOCK_RANGE,
+        }
+    }
+}
+
+pub(super) fn decode_event(log: &Log) -> Option<EscrowEvent> {
+    if let Ok(log) = log.log_decode::<FundsEscrowed>() {
+        return Some(EscrowEvent::FundsEscrowed {
+            intent_id: log.inner.data.intentId.to_string(),
+        });
+    }
+    if let Ok(log) = log.log_decode::<CrossChainMsgSolver>() {
+        let event = log.inner.data;
+        return Some(EscrowEvent::CrossChainMsgSolver {
+            intent_id: event.intentId.to_string(),
+            winner_solver: event.winnerSolver,
+            token: event.token,
+            user: event.user.to_string(),
+            amount: event.amount.to_string(),
+            solver_out: event.solverOut,
+        });
+    }
+    if let Ok(log) = log.log_decode::<CrossChainMsgUser>() {
+        let event = log.inner.data;
+        return Some(EscrowEvent::CrossChainMsgUser {
+            intent_id: event.intentId.to_string(),
+            user: event.user.to_string(),
+        });
+    }
+    None
+}
+
+/// Spawns a task following the escrow's events and returns the stream it feeds.
+///
+/// The websocket endpoint is only used to learn about new blocks. Logs are always fetched
+/// over HTTP for block ranges that are already confirmed, so the stream never has to retract
+/// an event, and the websocket and polling modes yield exactly the same events.
+pub(super) fn spawn_event_stream(
+    provider: RpcProvider,
+    ws_url: Url,
+    escrow: Address,
+    config: EventStreamConfig,
+) -> impl Stream<Item = Result<EscrowEventLog, ChainError>> {
+    let (sender, receiver) = mpsc::channel(64);
+    tokio::spawn(async move {
+        let mut follower = EventFollower {
+            provider,
+            escrow,
+            confirmations: config.confirmations,
+            max_block_range: config.max_block_range,
+            next_block: config.from_block,
+            sender,
+        };
+
+        // the websocket provider has to outlive its subscription
+        let mut heads = match ProviderBuilder::new().on_ws(WsConnect::new(ws_url.to_string())).await {
+            Ok(ws) => match ws.subscribe_blocks().await {
+                Ok(subscription) => Some((ws, subscription.into_stream())),
+                Err(_) => None,
+            },
+            Err(_) => None,
+        };
+
+        loop {
+            if !follower.poll().await {
+                return;
+            }
+            match &mut heads {
+                Some((_, stream)) => {
+                    if stream.next().await.is_none() {
+                        // the websocket dropped, keep going over HTTP
+                        heads = None;
+                    }
+                }
+                None => tokio::time::sleep(config.poll_interval).await,
+            }
+        }
+    });
+
+    futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|item| (item, receiver))
+    })
+}
+
+struct EventFollower {
+    provider: RpcProvider,
+    escrow: Address,
+    confirmations: u64,
+    max_block_range: u64,
+    /// First block whose logs haven't been yielded yet, `None` until the first poll.
+    next_block: Option<u64>,
+    sender: mpsc::Sender<Result<EscrowEventLog, ChainError>>,
+}
+
+impl EventFollower {
+    /// Yields the events of newly confirmed blocks, returns `false` once the stream is dropped.
+    async fn poll(&mut self) -> bool {
+        match self.send_confirmed_events().await {
+            Ok(open) => open,
+            Err(err) => self.sender.send(Err(err)).await.is_ok(),
+        }
+    }
+
+    /// Fetches the logs of the confirmed blocks not yielded yet, at most `max_block_range`
+    /// blocks per call, and sends their events. Progress is kept per range, so a failed call is
+    /// retried from that range on the next poll.
+    async fn send_confirmed_events(&mut self) -> Result<bool, ChainError> {
+        let latest = self
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| ChainError::BlockNumberError(e.to_string()))?;
+        let Some(confirmed) = latest.checked_sub(self.confirmations) else {
+            return Ok(true);
+        };
+        let from_block = *self.next_block.get_or_insert(confirmed + 1);
+
+        let filter = Filter::new()
+            .address(self.escrow)
+            .event_signature(vec![
+                FundsEscrowed::SIGNATURE_HASH,
+                CrossChainMsgSolver::SIGNATURE_HASH,
+                CrossChainMsgUser::SIGNATURE_HASH,
+            ]);
+        for (from, to) in block_ranges(from_block, confirmed, self.max_block_range) {
+            let logs = get_logs_in(&self.provider, &filter, from, to).await?;
+            for event in event_logs(&logs) {
+                if self.sender.send(Ok(event)).await.is_err() {
+                    return Ok(false);
+                }
+            }
+            self.next_block = Some(to + 1);
+        }
+        Ok(true)
+    }
+}
+
+fn event_logs(logs: &[Log]) -> Vec<EscrowEventLog> {
+    logs.iter()
+        .filter(|log| !log.removed)
+        .filter_map(|log| {
+            Some(EscrowEventLog {
+                event: decode_event(log)?,
+                tx_hash: log.transaction_hash?.to_string(),
+                block_number: log.block_number?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{LogData, TxHash, U256};
+
+    fn escrow_log(data: LogData) -> Log {
+        Log {
+            inner: alloy::primitives::Log {
+                address: Address::repeat_byte(1),
+                data,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn escrowed_log(intent_id: u64, block_number: u64) -> Log {
+        Log {
+            block_number: Some(block_number),
+            transaction_hash: Some(TxHash::repeat_byte(2)),
+            ..escrow_log(FundsEscrowed { intentId: U256::from(intent_id) }.encode_log_data())
+        }
+    }
+
+    #[test]
+    fn decode_event_decodes_cross_chain_messages() {
+        let user = Address::repeat_byte(3);
+        let solver_msg = escrow_log(
+            CrossChainMsgSolver {
+                intentId: U256::from(9),
+                winnerSolver: "0x0404040404040404040404040404040404040404".to_string(),
+                token: "0x0505050505050505050505050505050505050505".to_string(),
+                user,
+                amount: U256::from(500),
+                solverOut: "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T".to_string(),
+            }
+            .encode_log_data(),
+        );
+        assert_eq!(
+            decode_event(&solver_msg),
+            Some(EscrowEvent::CrossChainMsgSolver {
+                intent_id: "9".to_string(),
+                winner_solver: "0x0404040404040404040404040404040404040404".to_string(),
+                token: "0x0505050505050505050505050505050505050505".to_string(),
+                user: user.to_string(),
+                amount: "500".to_string(),
+                solver_out: "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T".to_string(),
+            })
+        );
+
+        let user_msg = escrow_log(CrossChainMsgUser { intentId: U256::from(9), user }.encode_log_data());
+        assert_eq!(
+            decode_event(&user_msg),
+            Some(EscrowEvent::CrossChainMsgUser {
+                intent_id: "9".to_string(),
+                user: user.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn event_logs_decodes_escrow_events() {
+        let events = event_logs(&[escrowed_log(7, 100)]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, EscrowEvent::FundsEscrowed { intent_id: "7".to_string() });
+        assert_eq!(events[0].block_number, 100);
+        assert_eq!(events[0].tx_hash, TxHash::repeat_byte(2).to_string());
+    }
+
+    #[test]
+    fn event_logs_skips_removed_and_pending_logs() {
+        let removed = Log { removed: true, ..escrowed_log(1, 100) };
+        let pending = Log { block_number: None, ..escrowed_log(2, 100) };
+        let events = event_logs(&[removed, pending, escrowed_log(3, 101)]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, EscrowEvent::FundsEscrowed { intent_id: "3".to_string() });
+    }
+}
@@ -0,0 +1,393 @@
+use crate::ethereum::ChainError;
+use alloy::network::TransactionBuilder;
+use alloy::primitives::TxHash;
+use alloy::providers::{PendingTransactionError, Provider, WatchTxError};
+use alloy::rpc::types::{TransactionReceipt, TransactionRequest};
+use alloy::transports::http::{Client, Http};
+use clap::ValueEnum;
+use std::time::Duration;
+
+/// How often the chain is checked while waiting for confirmations.
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Smallest fee increase, in percent, nodes accept for a replacement transaction.
+pub const MIN_FEE_BUMP_PERCENT: u64 = 10;
+
+/// How transaction fees are priced.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum FeeMode {
+    /// EIP-1559 max fee and priority fee, falling back to a legacy gas price on chains
+    /// without a base fee.
+    #[default]
+    Eip1559,
+    /// A single legacy gas price.
+    Legacy,
+}
+
+/// How [`EthereumClient`](crate::ethereum::EthereumClient) prices its transactions and
+/// waits for them to be included.
+#[derive(Clone, Debug)]
+pub struct EthFeeConfig {
+    pub mode: FeeMode,
+    /// Upper bound of the max fee per gas, or of the gas price in legacy mode, in wei.
+    pub max_fee_per_gas: Option<u128>,
+    /// Upper bound of the max priority fee per gas in wei, unused in legacy mode.
+    pub max_priority_fee_per_gas: Option<u128>,
+    /// Factor the estimated gas limit is multiplied with, at least 1.
+    pub gas_limit_multiplier: f64,
+    /// Blocks a transaction must be included in before it counts as confirmed, its own included.
+    pub confirmations: u64,
+    /// How long to wait for a transaction to be included before replacing it with higher
+    /// fees. Waiting for the remaining confirmations isn't limited.
+    pub timeout: Duration,
+    /// Percentage fees are raised by when replacing a transaction, at least
+    /// [`MIN_FEE_BUMP_PERCENT`] since nodes reject smaller bumps.
+    pub fee_bump_percent: u64,
+    /// How many times a transaction is replaced before giving up.
+    pub max_replacements: u32,
+}
+
+impl Default for EthFeeConfig {
+    fn default() -> Self {
+        Self {
+            mode: FeeMode::Eip1559,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            gas_limit_multiplier: 1.2,
+            confirmations: 1,
+            timeout: Duration::from_secs(60),
+            fee_bump_percent: 20,
+            max_replacements: 3,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Fees {
+    Legacy {
+        gas_price: u128,
+    },
+    Eip1559 {
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    },
+}
+
+impl Fees {
+    fn apply(&self, tx: &mut TransactionRequest) {
+        match *self {
+            Fees::Legacy { gas_price } => {
+                tx.set_gas_price(gas_price);
+                tx.max_fee_per_gas = None;
+                tx.max_priority_fee_per_gas = None;
+            }
+            Fees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                tx.gas_price = None;
+                tx.set_max_fee_per_gas(max_fee_per_gas);
+                tx.set_max_priority_fee_per_gas(max_priority_fee_per_gas);
+            }
+        }
+    }
+}
+
+impl EthFeeConfig {
+    /// Checks the gas limit isn't scaled below the estimate and replacements bump fees
+    /// enough for nodes to accept them.
+    pub fn validate(&self) -> Result<(), ChainError> {
+        if self.fee_bump_percent < MIN_FEE_BUMP_PERCENT {
+            return Err(ChainError::InvalidFeeConfig(format!(
+                "fee_bump_percent {} is below the {}% nodes require",
+                self.fee_bump_percent, MIN_FEE_BUMP_PERCENT
+            )));
+        }
+        if self.gas_limit_multiplier.is_nan() || self.gas_limit_multiplier < 1.0 {
+            return Err(ChainError::InvalidFeeConfig(format!(
+                "gas_limit_multiplier {} is below 1",
+                self.gas_limit_multiplier
+            )));
+        }
+        Ok(())
+    }
+
+    async fn estimate_fees<P: Provider<Http<Client>>>(&self, provider: &P) -> Result<Fees, ChainError> {
+        if self.mode == FeeMode::Eip1559 {
+            // fails on chains without a base fee
+            if let Ok(estimate) = provider.estimate_eip1559_fees(None).await {
+                let max_fee_per_gas = cap(estimate.max_fee_per_gas, self.max_fee_per_gas);
+                let max_priority_fee_per_gas =
+                    cap(estimate.max_priority_fee_per_gas, self.max_priority_fee_per_gas).min(max_fee_per_gas);
+                return Ok(Fees::Eip1559 {
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                });
+            }
+        }
+        let gas_price = provider.get_gas_price().await?;
+        Ok(Fees::Legacy {
+            gas_price: cap(gas_price, self.max_fee_per_gas),
+        })
+    }
+
+    /// Raises `fees` by `fee_bump_percent` within the caps, `None` if a cap doesn't leave
+    /// room for a replacement nodes accept.
+    fn bump(&self, fees: Fees) -> Option<Fees> {
+        let bump = |fee: u128, max: Option<u128>| {
+            cap(fee + (fee * self.fee_bump_percent as u128 / 100).max(1), max)
+        };
+        match fees {
+            Fees::Legacy { gas_price } => {
+                let bumped = bump(gas_price, self.max_fee_per_gas);
+                is_replacement_fee(gas_price, bumped).then_some(Fees::Legacy { gas_price: bumped })
+            }
+            Fees::Eip1559 {
+                max_fee_per_gas: fee,
+                max_priority_fee_per_gas: priority_fee,
+            } => {
+                let max_fee_per_gas = bump(fee, self.max_fee_per_gas);
+                let max_priority_fee_per_gas =
+                    bump(priority_fee, self.max_priority_fee_per_gas).min(max_fee_per_gas);
+                if !is_replacement_fee(fee, max_fee_per_gas)
+                    || !is_replacement_fee(priority_fee, max_priority_fee_per_gas)
+                {
+                    return None;
+                }
+                Some(Fees::Eip1559 {
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                })
+            }
+        }
+    }
+}
+
+fn cap(fee: u128, max: Option<u128>) -> u128 {
+    max.map_or(fee, |max| fee.min(max))
+}
+
+/// Tells whether nodes accept `bumped` as the replacement of a transaction paying `fee`:
+/// it must be higher and at least [`MIN_FEE_BUMP_PERCENT`] more.
+fn is_replacement_fee(fee: u128, bumped: u128) -> bool {
+    bumped > fee && bumped >= fee * (100 + MIN_FEE_BUMP_PERCENT as u128) / 100
+}
+
+/// Sends `tx` through `provider`, which fills in the nonce and signs it, and waits for its receipt.
+///
+/// The gas limit and fees are set according to `config`. A transaction that isn't included
+/// within `config.timeout` is replaced by one with the same nonce and bumped fees, up to
+/// `config.max_replacements` times. Whichever of them is included first is returned once it
+/// has `config.confirmations` confirmations.
+pub(super) async fn send_transaction<P: Provider<Http<Client>>>(
+    provider: &P,
+    mut tx: TransactionRequest,
+    config: &EthFeeConfig,
+) -> Result<TransactionReceipt, ChainError> {
+    config.validate()?;
+    let gas = provider.estimate_gas(&tx).await?;
+    tx.set_gas_limit((gas as f64 * config.gas_limit_multiplier).ceil() as u128);
+    let mut fees = config.estimate_fees(provider).await?;
+    fees.apply(&mut tx);
+
+    let mut sent: Vec<TxHash> = Vec::new();
+    let mut pending = provider.send_transaction(tx.clone()).await?;
+    loop {
+        let tx_hash = *pending.tx_hash();
+        sent.push(tx_hash);
+        let receipt = pending
+            .with_required_confirmations(1)
+            .with_timeout(Some(config.timeout))
+            .get_receipt()
+            .await;
+        match receipt {
+            Ok(receipt) => {
+                return wait_for_confirmations(provider, receipt, config.confirmations).await;
+            }
+            Err(PendingTransactionError::TxWatcher(WatchTxError::Timeout)) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        // a replaced transaction may have been included after all
+        for hash in &sent {
+            if let Some(receipt) = provider.get_transaction_receipt(*hash).await? {
+                return wait_for_confirmations(provider, receipt, config.confirmations).await;
+            }
+        }
+        let stuck = || ChainError::TransactionStuck {
+            tx_hash,
+            replacements: sent.len() as u32 - 1,
+        };
+        if sent.len() > config.max_replacements as usize {
+            return Err(stuck());
+        }
+        fees = config.bump(fees).ok_or_else(stuck)?;
+
+        if tx.nonce.is_none() {
+            // replacements must reuse the nonce the provider picked
+            let nonce = provider
+                .get_transaction_by_hash(tx_hash)
+                .await?
+                .ok_or_else(|| ChainError::TransactionInfoError(format!("Transaction {} not found", tx_hash)))?
+                .nonce;
+            tx.set_nonce(nonce);
+        }
+        fees.apply(&mut tx);
+        pending = provider.send_transaction(tx.clone()).await?;
+    }
+}
+
+/// Waits until the included transaction of `receipt` has `confirmations` confirmations and
+/// returns its receipt, read again in case a reorg moved it to another block.
+async fn wait_for_confirmations<P: Provider<Http<Client>>>(
+    provider: &P,
+    mut receipt: TransactionReceipt,
+    confirmations: u64,
+) -> Result<TransactionReceipt, ChainError> {
+    let tx_hash = receipt.transaction_hash;
+    loop {
+        let latest = provider
+            .get_block_number()
+            .await
+            .map_err(|e| ChainError::BlockNumberError(e.to_string()))?;
+        let included = receipt.block_number.ok_or_else(|| {
+            ChainError::TransactionInfoError(format!("Receipt of {} has no block", tx_hash))
+        })?;
+        if is_confirmed(included, latest, confirmations) {
+            return Ok(receipt);
+        }
+        tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+        receipt = provider.get_transaction_receipt(tx_hash).await?.ok_or_else(|| {
+            ChainError::TransactionInfoError(format!("Transaction {} was dropped by a reorg", tx_hash))
+        })?;
+    }
+}
+
+/// Tells whether a transaction included in block `included` has `confirmations`
+/// confirmations, its own block included, once the chain is at block `latest`.
+fn is_confirmed(included: u64, latest: u64, confirmations: u64) -> bool {
+    latest.saturating_add(1).saturating_sub(included) >= confirmations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_fee_per_gas: Option<u128>, max_priority_fee_per_gas: Option<u128>) -> EthFeeConfig {
+        EthFeeConfig {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            ..Default::default()
+        }
+    }
+
+    fn eip1559(max_fee_per_gas: u128, max_priority_fee_per_gas: u128) -> Fees {
+        Fees::Eip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        }
+    }
+
+    fn eip1559_fees(fees: Option<Fees>) -> Option<(u128, u128)> {
+        match fees? {
+            Fees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => Some((max_fee_per_gas, max_priority_fee_per_gas)),
+            Fees::Legacy { .. } => panic!("expected EIP-1559 fees"),
+        }
+    }
+
+    fn gas_price(fees: Option<Fees>) -> Option<u128> {
+        match fees? {
+            Fees::Legacy { gas_price } => Some(gas_price),
+            Fees::Eip1559 { .. } => panic!("expected a legacy gas price"),
+        }
+    }
+
+    #[test]
+    fn cap_limits_fees() {
+        assert_eq!(cap(100, None), 100);
+        assert_eq!(cap(100, Some(150)), 100);
+        assert_eq!(cap(100, Some(80)), 80);
+    }
+
+    #[test]
+    fn bump_raises_fees_by_the_percentage() {
+        let config = config(None, None);
+        assert_eq!(gas_price(config.bump(Fees::Legacy { gas_price: 100 })), Some(120));
+        assert_eq!(eip1559_fees(config.bump(eip1559(100, 10))), Some((120, 12)));
+        // tiny fees still go up
+        assert_eq!(eip1559_fees(config.bump(eip1559(1, 0))), Some((2, 1)));
+    }
+
+    #[test]
+    fn bump_stays_within_the_caps() {
+        let config = config(Some(110), Some(11));
+        assert_eq!(gas_price(config.bump(Fees::Legacy { gas_price: 100 })), Some(110));
+        assert_eq!(eip1559_fees(config.bump(eip1559(100, 10))), Some((110, 11)));
+    }
+
+    #[test]
+    fn bump_keeps_priority_fee_below_max_fee() {
+        let config = config(Some(110), None);
+        assert_eq!(eip1559_fees(config.bump(eip1559(100, 100))), Some((110, 110)));
+    }
+
+    #[test]
+    fn bump_is_stuck_at_the_caps() {
+        let config = config(Some(100), Some(10));
+        assert_eq!(gas_price(config.bump(Fees::Legacy { gas_price: 100 })), None);
+        assert_eq!(eip1559_fees(config.bump(eip1559(100, 5))), None);
+        assert_eq!(eip1559_fees(config.bump(eip1559(50, 10))), None);
+    }
+
+    #[test]
+    fn bump_needs_room_for_the_minimum_increase() {
+        // the caps leave room for +5% only, which nodes would reject
+        let max_fee_capped = config(Some(105), None);
+        assert_eq!(gas_price(max_fee_capped.bump(Fees::Legacy { gas_price: 100 })), None);
+        assert_eq!(eip1559_fees(max_fee_capped.bump(eip1559(100, 10))), None);
+        let priority_fee_capped = config(None, Some(100));
+        assert_eq!(eip1559_fees(priority_fee_capped.bump(eip1559(200, 95))), None);
+    }
+
+    #[test]
+    fn bump_needs_room_for_the_priority_fee_below_max_fee() {
+        // the max fee goes up enough, but the priority fee is held back by it
+        let config = config(Some(110), None);
+        assert_eq!(eip1559_fees(config.bump(eip1559(100, 105))), None);
+    }
+
+    #[test]
+    fn validate_rejects_replacements_nodes_refuse() {
+        assert!(EthFeeConfig::default().validate().is_ok());
+        let config = EthFeeConfig {
+            fee_bump_percent: 9,
+            ..Default::default()
+        };
+        assert!(matches!(config.validate(), Err(ChainError::InvalidFeeConfig(_))));
+        let config = EthFeeConfig {
+            gas_limit_multiplier: 0.9,
+            ..Default::default()
+        };
+        assert!(matches!(config.validate(), Err(ChainError::InvalidFeeConfig(_))));
+        let config = EthFeeConfig {
+            gas_limit_multiplier: f64::NAN,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn counts_confirmations_from_the_including_block() {
+        assert!(is_confirmed(100, 100, 1));
+        assert!(!is_confirmed(100, 100, 2));
+        assert!(is_confirmed(100, 111, 12));
+        assert!(!is_confirmed(100, 110, 12));
+        // the node may lag behind the block the receipt came from
+        assert!(!is_confirmed(100, 99, 1));
+        assert!(is_confirmed(100, 99, 0));
+    }
+}
@@ -0,0 +1,71 @@
+//! `eth_getLogs` over block ranges wider than RPC nodes serve in a single call.
+
+use crate::ethereum::ChainError;
+use alloy::providers::Provider;
+use alloy::rpc::types::{Filter, Log};
+use alloy::transports::http::{Client, Http};
+
+/// Blocks queried per `eth_getLogs` call. Providers cap the range of a call, commonly
+/// somewhere between 2,000 and 10,000 blocks.
+pub(super) const DEFAULT_MAX_BLOCK_RANGE: u64 = 2_000;
+
+/// Splits `from..=to` into consecutive ranges of at most `max_range` blocks, oldest first.
+pub(super) fn block_ranges(from: u64, to: u64, max_range: u64) -> Vec<(u64, u64)> {
+    let max_range = max_range.max(1);
+    let mut ranges = Vec::new();
+    let mut start = from;
+    while start <= to {
+        let end = start.saturating_add(max_range - 1).min(to);
+        ranges.push((start, end));
+        match end.checked_add(1) {
+            Some(next) => start = next,
+            None => break,
+        }
+    }
+    ranges
+}
+
+/// Fetches the logs matching `filter` in blocks `from..=to`, which must fit in one call.
+pub(super) async fn get_logs_in<P: Provider<Http<Client>>>(
+    provider: &P,
+    filter: &Filter,
+    from: u64,
+    to: u64,
+) -> Result<Vec<Log>, ChainError> {
+    let filter = filter.clone().from_block(from).to_block(to);
+    provider
+        .get_logs(&filter)
+        .await
+        .map_err(|e| ChainError::TransactionInfoError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_into_bounded_ranges() {
+        assert_eq!(block_ranges(0, 4_999, 2_000), vec![(0, 1_999), (2_000, 3_999), (4_000, 4_999)]);
+        assert_eq!(block_ranges(10, 10, 2_000), vec![(10, 10)]);
+        assert_eq!(block_ranges(10, 11, 1), vec![(10, 10), (11, 11)]);
+    }
+
+    #[test]
+    fn covers_every_block_once() {
+        let ranges = block_ranges(17, 1_234, 100);
+        assert_eq!(ranges.first().unwrap().0, 17);
+        assert_eq!(ranges.last().unwrap().1, 1_234);
+        for window in ranges.windows(2) {
+            assert_eq!(window[0].1 + 1, window[1].0);
+        }
+        assert!(ranges.iter().all(|(from, to)| to - from < 100));
+    }
+
+    #[test]
+    fn handles_empty_and_degenerate_ranges() {
+        assert!(block_ranges(11, 10, 2_000).is_empty());
+        // a zero range still makes progress
+        assert_eq!(block_ranges(0, 1, 0), vec![(0, 0), (1, 1)]);
+        assert_eq!(block_ranges(u64::MAX - 1, u64::MAX, 10), vec![(u64::MAX - 1, u64::MAX)]);
+    }
+}
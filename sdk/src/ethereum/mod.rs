@@ -1,11 +1,23 @@
 pub(crate) mod cli;
 mod client;
+pub mod erc20;
+mod events;
+mod fees;
+mod logs;
+mod revert;
+
+pub use client::{EthereumClient, Settlement};
+pub use events::EventStreamConfig;
+pub use fees::{EthFeeConfig, FeeMode, MIN_FEE_BUMP_PERCENT};
+pub use revert::EscrowRevert;
 
 use alloy::signers::local::coins_bip39::MnemonicError;
 use alloy::signers::local::LocalSignerError;
 use anchor_lang::prelude::thiserror::Error;
 use anchor_lang::solana_program::pubkey::ParsePubkeyError;
+use alloy::primitives::{Address, TxHash, U256};
 use clap::ValueEnum;
+use mantis_common::{Domain, EthereumNetwork};
 use reqwest::Url;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
@@ -31,6 +43,22 @@ impl Network {
         };
         (Url::parse(rpc).unwrap(), Url::parse(ws).unwrap())
     }
+
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            Network::EthereumMainnet => 1,
+            Network::EthereumSepolia => 11_155_111,
+        }
+    }
+}
+
+impl From<Network> for Domain {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::EthereumMainnet => Domain::Ethereum(EthereumNetwork::Mainnet),
+            Network::EthereumSepolia => Domain::Ethereum(EthereumNetwork::Sepolia),
+        }
+    }
 }
 
 #[allow(unused)]
@@ -60,12 +88,53 @@ pub enum ChainError {
     MnemonicError(#[from] MnemonicError),
     #[error("Local signer error: {0}")]
     LocalSignerError(#[from] LocalSignerError),
+    #[error("RPC node serves chain {actual}, but {network:?} is chain {expected}")]
+    ChainIdMismatch {
+        network: Network,
+        expected: u64,
+        actual: u64,
+    },
+    #[error("No escrow address configured for {0:?}, set one with with_escrow_address")]
+    MissingEscrowAddress(Network),
+    #[error("Intent {0} not found")]
+    IntentNotFound(String),
+    #[error("Balance {balance} of token {token} is lower than the required {required}")]
+    InsufficientBalance {
+        token: Address,
+        balance: U256,
+        required: U256,
+    },
+    #[error("Token {0} doesn't support EIP-2612 permits")]
+    PermitUnsupported(Address),
+    #[error("Auction of intent {0} hasn't picked a winner yet")]
+    AuctionPending(String),
+    #[error("Intent {intent_id} was won by {winner_solver}, not by the signer")]
+    NotWinnerSolver { intent_id: String, winner_solver: String },
+    #[error("Intent {intent_id} belongs to {src_user}, not to the signer")]
+    NotIntentUser { intent_id: String, src_user: String },
+    #[error("Intent {intent_id} can't be cancelled before its timeout {timeout}")]
+    IntentNotExpired { intent_id: String, timeout: u64 },
+    #[error("Intent source domain {actual} doesn't match the client's {expected}")]
+    DomainMismatch { expected: Domain, actual: Domain },
+    #[error("Intent is paid out on {actual:?}, not on the client's {expected}")]
+    DstDomainMismatch {
+        expected: Domain,
+        actual: Option<Domain>,
+    },
     #[error("Failed to parse ethereum Address")]
     ParseAddressError,
     #[error("Failed to parse uint: {0}")]
     ParseUintError(#[from] ruint::ParseError),
+    #[error("Escrow reverted: {0}")]
+    EscrowReverted(EscrowRevert),
     #[error("Contract error: {0}")]
     ContractError(#[from] alloy::contract::Error),
+    #[error("RPC error: {0}")]
+    RpcError(#[from] alloy::transports::TransportError),
+    #[error("Transaction {tx_hash} wasn't included, even after {replacements} fee bumps")]
+    TransactionStuck { tx_hash: TxHash, replacements: u32 },
+    #[error("Invalid fee config: {0}")]
+    InvalidFeeConfig(String),
     #[error("Pending transaction error: {0}")]
     PendingTransactionError(#[from] alloy::providers::PendingTransactionError),
     #[error("Other error: {0}")]
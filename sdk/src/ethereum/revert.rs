@@ -0,0 +1,172 @@
+use alloy::sol_types::{Revert, SolError};
+use alloy::transports::TransportError;
+use anchor_lang::prelude::thiserror::Error;
+
+/// A `require` of the escrow contract that failed, one variant per revert string in `escrow.sol`.
+#[derive(Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EscrowRevert {
+    #[error("Only the bridge contract can call this function")]
+    NotBridgeContract,
+    #[error("Bridged token denom doesn't contain the DUMMY token")]
+    MissingDummyToken,
+    #[error("Intent doesn't exist")]
+    IntentDoesNotExist,
+    #[error("Intent already exists")]
+    IntentAlreadyExists,
+    #[error("Bridged message sender isn't the intent's destination user or winning solver")]
+    DstUserNotFrom,
+    #[error("Bridged token isn't the intent's output token")]
+    TokenOutMismatch,
+    #[error("Bridged receiver isn't the intent's destination user")]
+    DstUserNotTo,
+    #[error("Bridged amount is lower than the intent's output amount")]
+    AmountOutTooHigh,
+    #[error("A new intent's winner solver must be empty")]
+    WinnerSolverNotEmpty,
+    #[error("Intent source user isn't the sender")]
+    SrcUserNotSender,
+    #[error("Sender isn't the intent's winning solver")]
+    NotWinnerSolver,
+    #[error("Sender isn't the intent's source user")]
+    NotIntentUser,
+    #[error("Intent hasn't timed out yet")]
+    IntentNotExpired,
+    #[error("Invalid full format of a bridged message")]
+    InvalidFullFormat,
+    #[error("Invalid short format of a bridged message")]
+    InvalidShortFormat,
+    #[error("Bridge call failed")]
+    CallFailed,
+    #[error("Only the owner can call this function")]
+    NotOwner,
+}
+
+impl EscrowRevert {
+    /// Returns the revert string the escrow fails with.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            EscrowRevert::NotBridgeContract => "msg.sender != BRIDGE_CONTRACT",
+            EscrowRevert::MissingDummyToken => "denom doesn't have DUMMY token",
+            EscrowRevert::IntentDoesNotExist => "intent doesn't exist",
+            EscrowRevert::IntentAlreadyExists => "intent already exist",
+            EscrowRevert::DstUserNotFrom => "intent.dstUser != from",
+            EscrowRevert::TokenOutMismatch => "intent.tokenOut != token",
+            EscrowRevert::DstUserNotTo => "intent.dstUser != to",
+            EscrowRevert::AmountOutTooHigh => "intent.amountOut > amount",
+            EscrowRevert::WinnerSolverNotEmpty => "winnerSolver must be empty string",
+            EscrowRevert::SrcUserNotSender => "newIntentInfo.src_user != msg.sender",
+            EscrowRevert::NotWinnerSolver => "intent.winnerSolver != msg.sender",
+            EscrowRevert::NotIntentUser => "intent.srcUser != msg.sender",
+            EscrowRevert::IntentNotExpired => "intent.timeout > block.timestamp",
+            EscrowRevert::InvalidFullFormat => "Invalid full format",
+            EscrowRevert::InvalidShortFormat => "Invalid short format",
+            EscrowRevert::CallFailed => "call failed!",
+            EscrowRevert::NotOwner => "Only the owner can call this function",
+        }
+    }
+
+    /// Every variant, kept complete by the tests against `escrow.sol`.
+    const ALL: [EscrowRevert; 17] = [
+        EscrowRevert::NotBridgeContract,
+        EscrowRevert::MissingDummyToken,
+        EscrowRevert::IntentDoesNotExist,
+        EscrowRevert::IntentAlreadyExists,
+        EscrowRevert::DstUserNotFrom,
+        EscrowRevert::TokenOutMismatch,
+        EscrowRevert::DstUserNotTo,
+        EscrowRevert::AmountOutTooHigh,
+        EscrowRevert::WinnerSolverNotEmpty,
+        EscrowRevert::SrcUserNotSender,
+        EscrowRevert::NotWinnerSolver,
+        EscrowRevert::NotIntentUser,
+        EscrowRevert::IntentNotExpired,
+        EscrowRevert::InvalidFullFormat,
+        EscrowRevert::InvalidShortFormat,
+        EscrowRevert::CallFailed,
+        EscrowRevert::NotOwner,
+    ];
+
+    /// Returns the variant reverting with `reason`, `None` if the escrow has no such `require`.
+    pub fn from_reason(reason: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|revert| revert.reason() == reason)
+    }
+}
+
+/// Returns the reason of a reverted call, `None` if `err` isn't a revert.
+pub(super) fn revert_reason(err: &TransportError) -> Option<String> {
+    let payload = err.as_error_resp()?;
+    if let Some(data) = payload.as_revert_data() {
+        // custom errors aren't known here, keep their raw data
+        return Some(Revert::abi_decode(&data, false).map_or_else(|_| data.to_string(), |revert| revert.reason));
+    }
+    // some nodes only return the reason in the message
+    let reason = payload.message.strip_prefix("execution reverted")?;
+    Some(reason.trim_start_matches(':').trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Returns the revert strings of all `require`s in `source`.
+    fn require_reasons(source: &str) -> HashSet<&str> {
+        source
+            .split("require(")
+            .skip(1)
+            .map(|call| {
+                let call = &call[..call.find(");").unwrap()];
+                let end = call.rfind('"').unwrap();
+                let start = call[..end].rfind('"').unwrap();
+                &call[start + 1..end]
+            })
+            .collect()
+    }
+
+    /// Returns the error a node answers a reverting `eth_call` with.
+    fn revert_error(message: &str, data: Option<&str>) -> TransportError {
+        let payload = serde_json::json!({ "code": 3, "message": message, "data": data });
+        TransportError::ErrorResp(serde_json::from_str(&payload.to_string()).unwrap())
+    }
+
+    #[test]
+    fn maps_every_require_of_the_escrow() {
+        let reasons = require_reasons(include_str!("../../../contracts/ethereum/escrow.sol"));
+        for reason in &reasons {
+            let revert = EscrowRevert::from_reason(reason);
+            assert_eq!(revert.map(|revert| revert.reason()), Some(*reason), "{} isn't mapped", reason);
+        }
+        let mapped: HashSet<&str> = EscrowRevert::ALL.iter().map(EscrowRevert::reason).collect();
+        assert_eq!(mapped.len(), EscrowRevert::ALL.len(), "reasons aren't unique");
+        assert_eq!(mapped, reasons);
+    }
+
+    #[test]
+    fn decodes_abi_encoded_error_strings() {
+        // Error(string) with reason "intent doesn't exist"
+        let data = "0x08c379a0\
+            0000000000000000000000000000000000000000000000000000000000000020\
+            0000000000000000000000000000000000000000000000000000000000000014\
+            696e74656e7420646f65736e2774206578697374000000000000000000000000";
+        let err = revert_error("execution reverted", Some(data));
+        assert_eq!(revert_reason(&err).as_deref(), Some("intent doesn't exist"));
+        assert_eq!(
+            revert_reason(&err).and_then(|reason| EscrowRevert::from_reason(&reason)),
+            Some(EscrowRevert::IntentDoesNotExist)
+        );
+    }
+
+    #[test]
+    fn keeps_custom_error_data() {
+        let err = revert_error("execution reverted", Some("0xfb8f41b2"));
+        assert_eq!(revert_reason(&err).as_deref(), Some("0xfb8f41b2"));
+        assert_eq!(EscrowRevert::from_reason("0xfb8f41b2"), None);
+    }
+
+    #[test]
+    fn reads_reasons_from_the_message() {
+        let err = revert_error("execution reverted: intent.timeout > block.timestamp", None);
+        assert_eq!(revert_reason(&err).as_deref(), Some("intent.timeout > block.timestamp"));
+        assert_eq!(revert_reason(&revert_error("nonce too low", None)), None);
+    }
+}
@@ -0,0 +1,40 @@
+/// Event emitted by the escrow contract, normalized across chains.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EscrowEvent {
+    /// User funds were escrowed for a new intent.
+    FundsEscrowed { intent_id: String },
+    /// A solver paid out a cross-domain intent, the escrow is released once the bridge confirms.
+    CrossChainMsgSolver {
+        intent_id: String,
+        winner_solver: String,
+        token: String,
+        user: String,
+        amount: String,
+        solver_out: String,
+    },
+    /// A user cancelled a cross-domain intent, the refund is sent once the bridge confirms.
+    CrossChainMsgUser { intent_id: String, user: String },
+    /// The auctioneer recorded the auction result of an intent. Only emitted on Solana.
+    AuctionUpdated {
+        intent_id: String,
+        amount_out: String,
+        winner_solver: String,
+    },
+    /// The winning solver settled a single domain intent. Only emitted on Solana.
+    IntentSettled {
+        intent_id: String,
+        solver: String,
+        amount_out: String,
+    },
+    /// The user cancelled an expired intent and got the escrowed tokens back. Only emitted on Solana.
+    IntentCancelled { intent_id: String, user: String },
+}
+
+/// An [`EscrowEvent`] together with the transaction that emitted it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowEventLog {
+    pub event: EscrowEvent,
+    pub tx_hash: String,
+    /// Block, or slot on Solana, the transaction was included in.
+    pub block_number: u64,
+}
@@ -1,11 +1,13 @@
 use async_trait::async_trait;
-use mantis_common::UserIntent;
-use solana_sdk::account_info::AccountInfo;
-use solana_sdk::instruction::Instruction;
+use mantis_common::{Domain, Intent, IntentStatus, UserIntent};
 
 pub mod cmd;
-mod ethereum;
-mod solana;
+pub mod ethereum;
+pub mod events;
+pub mod quote;
+pub mod solana;
+pub mod tokens;
+pub mod transaction;
 
 #[allow(unused)]
 enum Network {
@@ -13,15 +15,67 @@ enum Network {
     Ethereum(ethereum::Network),
 }
 
-pub trait EscrowContract {
-    type Error;
-    type Input<'a>;
+/// Result of a successful [`Chain::submit_intent`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubmitReceipt {
+    /// Transaction hash on Ethereum, transaction signature on Solana.
+    pub tx_hash: String,
+    /// Block number (slot on Solana) the transaction landed in, if known.
+    pub block_number: Option<u64>,
+    /// Id under which the escrow stored the intent.
+    pub intent_id: String,
+}
+
+/// Result of a successful [`Chain::cancel_intent`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CancelReceipt {
+    /// Transaction hash on Ethereum, transaction signature on Solana.
+    pub tx_hash: String,
+    pub outcome: CancelOutcome,
+}
 
-    fn instruction(&self) -> Result<(Instruction, Vec<AccountInfo>), Self::Error>;
+/// Result of a successful [`Chain::settle_intent`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SettleReceipt {
+    /// Transaction hash on Ethereum, transaction signature on Solana.
+    pub tx_hash: String,
+    /// Block number (slot on Solana) the transaction landed in, if known.
+    pub block_number: Option<u64>,
+}
+
+/// How the escrowed tokens of a cancelled intent get back to the user.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CancelOutcome {
+    /// The escrow refunded the tokens directly.
+    Refunded,
+    /// The intent is cross-domain: the escrow emitted `CrossChainMsgUser` and refunds
+    /// the tokens once the bridge confirms.
+    CrossChainMsgUser,
+    /// The signer already requested the cross-domain refund, nothing was sent. `tx_hash` is
+    /// the earlier request's transaction.
+    AlreadyRequested,
+}
 
-    fn submit_intent(&self, params: UserIntent) -> Result<(), Self::Error>;
+/// Outcome of simulating a write against the chain's current state without sending it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulationReport {
+    /// Whether the transaction would succeed.
+    pub success: bool,
+    /// Revert reason on Ethereum, transaction error on Solana, if it would fail.
+    pub error: Option<String>,
+    /// Estimated gas on Ethereum, compute units consumed on Solana.
+    pub gas_used: Option<u64>,
+    /// Program logs of the simulation, only returned by Solana.
+    pub logs: Vec<String>,
+    /// Token the escrow would be approved to spend first, Ethereum only. The escrow call is
+    /// then simulated as if the approval was sent already.
+    pub approval: Option<String>,
+    /// Whether the escrow call itself was simulated. If the approval can't be simulated away,
+    /// the report covers the approval instead and this is `false`.
+    pub escrow_call_verified: bool,
 }
 
+/// Intent lifecycle operations shared by all supported chains.
 #[async_trait]
 pub trait Chain {
     type Transaction;
@@ -32,37 +86,45 @@ pub trait Chain {
 
     async fn get_transaction(&self, tx_hash: &str) -> Result<Self::Transaction, Self::Error>;
 
-    // async fn process_transaction(
-    //     &self,
-    //     params: TransactionParams,
-    // ) -> Result<bool, Self::Error>;
-    //
-    // async fn transfer(
-    //     &self,
-    //     recipient: &Self::Address,
-    //     token: &Self::Token,
-    //     amount: Self::Amount,
-    // ) -> Result<String, Self::Error>;
-    //
-    // async fn create_token_account(
-    //     &self,
-    //     owner: &Self::Address,
-    //     token: &Self::Token,
-    // ) -> Result<(), Self::Error>;
-    //
-    // async fn get_current_block_number(&self) -> Result<u64, Self::Error>;
-
-    async fn submit_intent(
+    /// Returns the current block number (slot on Solana).
+    async fn get_current_block_number(&self) -> Result<u64, Self::Error>;
+
+    /// Returns the decimals of `token` (ERC-20 address, SPL mint) on this chain.
+    async fn token_decimals(&self, token: &str) -> Result<u8, Self::Error>;
+
+    /// Escrows the user's input tokens and stores the intent on chain.
+    async fn submit_intent(&self, intent: UserIntent) -> Result<SubmitReceipt, Self::Error>;
+
+    /// Simulates [`Chain::submit_intent`] without sending anything.
+    ///
+    /// Checks done before sending fail the same way, on-chain failures are reported.
+    async fn simulate_submit_intent(&self, intent: UserIntent) -> Result<SimulationReport, Self::Error>;
+
+    /// Returns the stored intent, or `None` if it doesn't exist (anymore).
+    async fn get_intent(&self, intent_id: &str) -> Result<Option<Intent>, Self::Error>;
+
+    /// Returns where the intent is in its lifecycle, including after it was settled or cancelled.
+    async fn intent_status(&self, intent_id: &str) -> Result<IntentStatus, Self::Error>;
+
+    /// Cancels a timed out intent of the signer, refunding the escrowed tokens to the user.
+    ///
+    /// `dst_domain` is the domain the intent was submitted to pay out on, it decides whether
+    /// the refund is direct or goes through the bridge. The Ethereum escrow doesn't record it.
+    /// Checks the signer is the intent's `src_user` and the intent timed out before sending.
+    async fn cancel_intent(&self, intent_id: &str, dst_domain: Domain) -> Result<CancelReceipt, Self::Error>;
+
+    /// Simulates [`Chain::cancel_intent`] without sending anything.
+    async fn simulate_cancel_intent(
         &self,
-        intent: UserIntent,
-        address: Self::Address,
-    ) -> Result<(), Self::Error>;
-
-    // async fn on_receive_transfer(
-    //     &self,
-    //     params: ReceiveTransferParams,
-    //     program_id: Self::Address,
-    // ) -> Result<(), Self::Error>;
+        intent_id: &str,
+        dst_domain: Domain,
+    ) -> Result<SimulationReport, Self::Error>;
+
+    /// Settles an intent as its winning solver: pays the user and releases the escrow to the solver.
+    async fn settle_intent(&self, intent_id: &str) -> Result<SettleReceipt, Self::Error>;
+
+    /// Simulates [`Chain::settle_intent`] without sending anything.
+    async fn simulate_settle_intent(&self, intent_id: &str) -> Result<SimulationReport, Self::Error>;
 
     fn signer(&self) -> Self::Address;
 }
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::thiserror::Error;
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+
+/// Swap to be priced by a [`QuoteProvider`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteRequest {
+    pub token_in: String,
+    pub token_out: String,
+    pub amount_in: String,
+}
+
+/// Expected outcome of a swap, as estimated by the auctioneer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quote {
+    /// Expected amount of `token_out` received.
+    pub amount_out: String,
+    /// Price of one `token_in` in `token_out`.
+    pub price: String,
+    #[serde(default)]
+    pub fees: Vec<QuoteFee>,
+    /// Hops the swap is expected to take, in order.
+    #[serde(default)]
+    pub route: Vec<RouteHop>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteFee {
+    /// What the fee is charged for, e.g. "solver" or "bridge".
+    pub kind: String,
+    pub token: String,
+    pub amount: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteHop {
+    /// Chain or venue the hop executes on.
+    pub venue: String,
+    pub token_in: String,
+    pub token_out: String,
+}
+
+/// Source of swap quotes.
+#[async_trait]
+pub trait QuoteProvider {
+    type Error;
+
+    async fn get_quote(&self, request: &QuoteRequest) -> Result<Quote, Self::Error>;
+}
+
+#[derive(Error, Debug)]
+pub enum QuoteError {
+    #[error("Quote request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Quote endpoint returned {status}: {body}")]
+    ResponseError { status: StatusCode, body: String },
+}
+
+/// Fetches quotes from the Mantis auctioneer's JSON-over-HTTP quote endpoint.
+///
+/// The [`QuoteRequest`] is POSTed as JSON and a [`Quote`] is expected back.
+pub struct HttpQuoteProvider {
+    client: Client,
+    url: Url,
+}
+
+impl HttpQuoteProvider {
+    pub fn new(url: Url) -> Self {
+        Self::new_with_client(Client::new(), url)
+    }
+
+    pub fn new_with_client(client: Client, url: Url) -> Self {
+        Self { client, url }
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for HttpQuoteProvider {
+    type Error = QuoteError;
+
+    async fn get_quote(&self, request: &QuoteRequest) -> Result<Quote, Self::Error> {
+        let response = self.client.post(self.url.clone()).json(request).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(QuoteError::ResponseError { status, body });
+        }
+        Ok(response.json().await?)
+    }
+}
@@ -1,9 +1,11 @@
 use std::sync::Arc;
 use crate::solana::{ChainError, Cluster};
+use anchor_lang::prelude::Pubkey;
 use clap::Args;
+use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signer::SeedDerivable;
-use crate::solana::client::SolanaClient;
+use crate::solana::client::{SolanaClient, SolanaSendConfig};
 
 // TODO: improve keypair handling (load from raw keys, files, interactive, etc.)
 #[derive(Args)]
@@ -20,28 +22,90 @@ pub(crate) struct SolanaArgs {
     pub(crate) ws_url: Option<String>,
     #[arg(long, env = "SOLANA_CLUSTER")]
     pub(crate) cluster: Cluster,
+    /// Escrow program id, overriding the cluster default
+    #[arg(long, env = "SOLANA_ESCROW_ADDRESS")]
+    pub(crate) escrow_address: Option<Pubkey>,
+    /// Commitment sent transactions must reach (processed, confirmed or finalized), reads
+    /// use confirmed at least
+    #[arg(long, env = "SOLANA_COMMITMENT", default_value = "confirmed")]
+    pub(crate) commitment: CommitmentConfig,
+    /// Send transactions without simulating them first
+    #[arg(long, env = "SOLANA_SKIP_PREFLIGHT")]
+    pub(crate) skip_preflight: bool,
+    /// How many times the RPC node retries forwarding a transaction
+    #[arg(long, env = "SOLANA_MAX_RETRIES")]
+    pub(crate) max_retries: Option<usize>,
+    /// Compute unit limit of sent transactions
+    #[arg(long, env = "SOLANA_COMPUTE_UNIT_LIMIT")]
+    pub(crate) compute_unit_limit: Option<u32>,
+    /// Priority fee in micro-lamports per compute unit
+    #[arg(long, env = "SOLANA_COMPUTE_UNIT_PRICE")]
+    pub(crate) compute_unit_price: Option<u64>,
+}
+
+/// Connection to the cluster an intent settled on another chain is escrowed on.
+#[derive(Args)]
+pub(crate) struct SolanaSourceArgs {
+    /// Cluster the intent is escrowed on, to settle a Solana intent cross-chain
+    #[arg(id = "src_cluster", long = "src-cluster", requires = "solver_out")]
+    pub(crate) cluster: Option<Cluster>,
+    #[arg(id = "src_rpc_url", long = "src-rpc-url", requires_all = ["src_ws_url", "src_cluster"])]
+    pub(crate) rpc_url: Option<String>,
+    #[arg(id = "src_ws_url", long = "src-ws-url", requires_all = ["src_rpc_url", "src_cluster"])]
+    pub(crate) ws_url: Option<String>,
+    /// Escrow program id on the source cluster, overriding the cluster default
+    #[arg(id = "src_escrow_address", long = "src-escrow-address", requires = "src_cluster")]
+    pub(crate) escrow_address: Option<Pubkey>,
+}
+
+impl SolanaSourceArgs {
+    /// Builds a client of the source cluster, `None` if none was given. It only reads
+    /// intents, so it signs with a throwaway keypair.
+    pub(crate) fn build_reader(&self) -> Option<SolanaClient> {
+        let cluster = self.cluster?;
+        Some(connect(
+            cluster,
+            Arc::new(Keypair::new()),
+            self.rpc_url.as_deref().zip(self.ws_url.as_deref()),
+            self.escrow_address,
+        ))
+    }
+}
+
+/// Connects to `cluster` through `urls` (RPC and websocket), or its default RPCs.
+fn connect(
+    cluster: Cluster,
+    keypair: Arc<Keypair>,
+    urls: Option<(&str, &str)>,
+    escrow_address: Option<Pubkey>,
+) -> SolanaClient {
+    let client = match urls {
+        Some((rpc_url, ws_url)) => SolanaClient::new_with_url(cluster, keypair, rpc_url, ws_url),
+        // if RPCs are not provided, use default ones depending on the cluster
+        None => SolanaClient::new(cluster, keypair),
+    };
+    match escrow_address {
+        Some(escrow_address) => client.with_escrow_address(escrow_address),
+        None => client,
+    }
 }
 
 impl SolanaArgs {
     pub(crate) async fn build_client(&self) -> Result<SolanaClient, ChainError> {
         let keypair = Arc::new(self.build_signer()?);
-
-        let client = if self.rpc_url.is_none() {
-            // if RPCs are not provided, use default ones depending on the cluster
-            SolanaClient::new(self.cluster, keypair.clone())
-        } else {
-            let (rpc_url, ws_url) = (
-                self.rpc_url.as_ref().unwrap(),
-                self.ws_url.as_ref().unwrap(),
-            );
-            SolanaClient::new_with_url(
-                self.cluster,
-                keypair.clone(),
-                rpc_url,
-                ws_url,
-            )
-        };
-        Ok(client)
+        let client = connect(
+            self.cluster,
+            keypair,
+            self.rpc_url.as_deref().zip(self.ws_url.as_deref()),
+            self.escrow_address,
+        );
+        Ok(client.with_send_config(SolanaSendConfig {
+            commitment: self.commitment,
+            skip_preflight: self.skip_preflight,
+            max_retries: self.max_retries,
+            compute_unit_limit: self.compute_unit_limit,
+            compute_unit_price: self.compute_unit_price,
+        }))
     }
 
     pub fn build_signer(&self) -> Result<Keypair, ChainError> {
@@ -1,20 +1,127 @@
+use crate::events::EscrowEventLog;
+use crate::solana::events::{decode_logs, spawn_event_stream, EventStreamConfig};
+use crate::solana::read_commitment;
 use crate::solana::{ChainError, Cluster};
-use crate::Chain;
-use anchor_client::Cluster as SolanaCluster;
+use crate::transaction::{EscrowCall, TransactionStatus, TransactionSummary};
+use crate::{CancelOutcome, CancelReceipt, Chain, SettleReceipt, SimulationReport, SubmitReceipt};
+use anchor_client::{Cluster as SolanaCluster, Program};
 use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::Mint;
 use async_trait::async_trait;
-use mantis_common::UserIntent;
-use solana_client::rpc_client::RpcClient;
-use solana_client::rpc_config::RpcSendTransactionConfig;
+use futures::Stream;
+use anchor_lang::AccountDeserialize;
+use mantis_common::{Domain, Intent, IntentStatus, TokenAmount, UserIntent};
+use mantis_escrow_program::instruction as escrow_instruction;
+use mantis_escrow_program::{EscrowError, IntentState};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig, RpcTransactionConfig};
+use solana_sdk::account::from_account;
+use solana_sdk::bpf_loader_upgradeable;
+use solana_sdk::bs58;
+use solana_sdk::clock::Clock;
 use solana_sdk::commitment_config::CommitmentConfig;
-use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::sysvar;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_transaction_status::{
+    EncodedTransaction, UiInstruction, UiMessage, UiParsedInstruction, UiTransactionEncoding,
+};
+use ruint::aliases::U256;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-pub(crate) struct SolanaClient {
+/// Errors of the escrow program in declaration order, to map custom instruction error codes
+/// back to them. Kept complete by the tests against the program's source.
+const ESCROW_ERRORS: [EscrowError; 18] = [
+    EscrowError::InvalidIntentId,
+    EscrowError::InvalidAmount,
+    EscrowError::AmountMismatch,
+    EscrowError::DecimalsMismatch,
+    EscrowError::SrcUserMismatch,
+    EscrowError::TokenInMismatch,
+    EscrowError::TokenOutTooLong,
+    EscrowError::TokenOutMismatch,
+    EscrowError::WinnerSolverMismatch,
+    EscrowError::WinnerSolverTooLong,
+    EscrowError::NotAuctioneer,
+    EscrowError::IntentNotExpired,
+    EscrowError::NotUpgradeAuthority,
+    EscrowError::DstUserTooLong,
+    EscrowError::DstUserMismatch,
+    EscrowError::AuctionAlreadyWon,
+    EscrowError::IntentExpired,
+    EscrowError::CrossDomainUnsupported,
+];
+
+/// How [`SolanaClient`] sends transactions.
+#[derive(Clone, Debug)]
+pub struct SolanaSendConfig {
+    /// Commitment a transaction must reach before it's considered confirmed, also used
+    /// for preflight checks. Reads use it too, but at least `confirmed`.
+    pub commitment: CommitmentConfig,
+    /// Send transactions without simulating them first. Failing transactions then still
+    /// land and pay fees, and their error only shows once they're confirmed.
+    pub skip_preflight: bool,
+    /// How many times the RPC node retries forwarding a transaction to the leader,
+    /// the node's default if `None`.
+    pub max_retries: Option<usize>,
+    /// Compute units to request, the runtime's default if `None`.
+    pub compute_unit_limit: Option<u32>,
+    /// Priority fee in micro-lamports per compute unit, none if `None`.
+    pub compute_unit_price: Option<u64>,
+}
+
+impl Default for SolanaSendConfig {
+    fn default() -> Self {
+        Self {
+            commitment: CommitmentConfig::confirmed(),
+            skip_preflight: false,
+            max_retries: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+        }
+    }
+}
+
+impl SolanaSendConfig {
+    /// Returns the compute budget instructions to prepend to every transaction.
+    fn compute_budget_instructions(&self) -> Vec<Instruction> {
+        let limit = self.compute_unit_limit.map(ComputeBudgetInstruction::set_compute_unit_limit);
+        let price = self.compute_unit_price.map(ComputeBudgetInstruction::set_compute_unit_price);
+        limit.into_iter().chain(price).collect()
+    }
+}
+
+/// How [`SolanaClient`] waits for the transactions it sends to be confirmed.
+#[derive(Clone, Debug)]
+pub struct ConfirmationConfig {
+    /// How often to check the transaction's status.
+    pub poll_interval: Duration,
+    /// How long to wait before giving up with [`ChainError::ConfirmationTimeout`].
+    pub timeout: Duration,
+}
+
+impl Default for ConfirmationConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_millis(500),
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+pub struct SolanaClient {
     rpc_client: RpcClient,
     sender_keypair: Arc<Keypair>,
-    #[allow(unused)]
+    ws_url: String,
+    program_id: Pubkey,
     network: Cluster,
+    send_config: SolanaSendConfig,
+    confirmation: ConfirmationConfig,
 }
 
 impl SolanaClient {
@@ -27,74 +134,758 @@ impl SolanaClient {
         network: Cluster,
         sender_keypair: Arc<Keypair>,
         rpc_url: &str,
-        _ws_url: &str,
+        ws_url: &str,
     ) -> Self {
-        let rpc_client = RpcClient::new(rpc_url.to_string());
+        let rpc_client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
 
         Self {
             rpc_client,
             sender_keypair,
+            ws_url: ws_url.to_string(),
+            program_id: network.default_escrow_program_id(),
             network,
+            send_config: SolanaSendConfig::default(),
+            confirmation: ConfirmationConfig::default(),
+        }
+    }
+
+    /// Sends transactions according to `send_config` instead of the defaults.
+    pub fn with_send_config(mut self, send_config: SolanaSendConfig) -> Self {
+        self.rpc_client = RpcClient::new_with_commitment(
+            self.rpc_client.url(),
+            read_commitment(send_config.commitment),
+        );
+        self.send_config = send_config;
+        self
+    }
+
+    /// Waits for sent transactions according to `confirmation` instead of the defaults.
+    pub fn with_confirmation(mut self, confirmation: ConfirmationConfig) -> Self {
+        self.confirmation = confirmation;
+        self
+    }
+
+    /// Targets the escrow program deployed at `program_id` instead of the cluster default.
+    pub fn with_escrow_address(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    /// Streams the escrow program's events, decoded into the same [`EscrowEvent`](crate::events::EscrowEvent)s
+    /// as on Ethereum.
+    ///
+    /// Transactions are received over `logsSubscribe` on the websocket endpoint, falling back to
+    /// polling `getSignaturesForAddress` if it can't be reached or drops. Events are only yielded
+    /// once their transaction reached `config.commitment`. RPC failures are yielded as errors
+    /// and retried on the next poll; the stream ends when it's dropped.
+    pub fn escrow_events(
+        &self,
+        config: EventStreamConfig,
+    ) -> impl Stream<Item = Result<EscrowEventLog, ChainError>> {
+        spawn_event_stream(
+            self.rpc_client.url(),
+            self.ws_url.clone(),
+            self.program_id,
+            config,
+        )
+    }
+
+    /// Creates the escrow program's auctioneer account with this client's signer as authority.
+    /// The signer must be the escrow program's upgrade authority.
+    pub async fn initialize_auctioneer(&self) -> Result<(), ChainError> {
+        let authority = self.sender_keypair.clone();
+        let program = self.program()?;
+
+        let instructions = program
+            .request()
+            .accounts(mantis_escrow_program::accounts::InitializeAuctioneer {
+                authority: authority.pubkey(),
+                auctioneer: self.auctioneer_address(),
+                program_data: self.program_data_address(),
+                system_program: anchor_lang::system_program::ID,
+            })
+            .args(mantis_escrow_program::instruction::InitializeAuctioneer {})
+            .instructions()
+            .map_err(|e| ChainError::Other(format!("Failed to build instruction: {}", e)))?;
+
+        self.send_instructions(&instructions).await.map(|_| ())
+    }
+
+    /// Publishes the auction result for an intent. The signer must be the auctioneer authority,
+    /// the intent must not have a winner yet and must not have timed out.
+    pub async fn update_auction_data(
+        &self,
+        intent_id: &str,
+        amount_out: u128,
+        winner_solver: String,
+    ) -> Result<(), ChainError> {
+        let authority = self.sender_keypair.clone();
+        let program = self.program()?;
+
+        let instructions = program
+            .request()
+            .accounts(mantis_escrow_program::accounts::UpdateAuctionData {
+                authority: authority.pubkey(),
+                auctioneer: self.auctioneer_address(),
+                intent: self.intent_address(intent_id),
+            })
+            .args(mantis_escrow_program::instruction::UpdateAuctionData {
+                _intent_id: intent_id.to_string(),
+                amount_out,
+                winner_solver,
+            })
+            .instructions()
+            .map_err(|e| ChainError::Other(format!("Failed to build instruction: {}", e)))?;
+
+        self.send_instructions(&instructions).await.map(|_| ())
+    }
+
+    /// Settles a single domain intent as its winning solver: pays the intent's
+    /// `amount_out` to the user and receives the escrowed input tokens.
+    pub async fn send_funds_to_user(&self, intent_id: &str) -> Result<SettleReceipt, ChainError> {
+        let instructions = self.send_funds_to_user_instructions(intent_id).await?;
+        let (signature, slot) = self.send_instructions(&instructions).await?;
+        Ok(SettleReceipt {
+            tx_hash: signature.to_string(),
+            block_number: Some(slot),
+        })
+    }
+
+    /// Builds the `send_funds_to_user` instruction settling the intent as the signer.
+    async fn send_funds_to_user_instructions(&self, intent_id: &str) -> Result<Vec<Instruction>, ChainError> {
+        let solver = self.sender_keypair.clone();
+        let program = self.program()?;
+
+        let intent_state = self.intent_address(intent_id);
+        let intent = program.account::<IntentState>(intent_state).await.map_err(|e| {
+            ChainError::TransactionProcessingError(format!("Failed to get intent {}: {}", intent_id, e))
+        })?;
+
+        let token_out_mint: Pubkey = intent.token_out.parse()?;
+        let token_in_program = self.token_program(&intent.token_in).await?;
+        let token_out_program = self.token_program(&token_out_mint).await?;
+
+        program
+            .request()
+            .accounts(mantis_escrow_program::accounts::SendFundsToUser {
+                solver: solver.pubkey(),
+                user: intent.user,
+                intent: intent_state,
+                token_in_mint: intent.token_in,
+                escrow_token_account: get_associated_token_address_with_program_id(
+                    &intent_state,
+                    &intent.token_in,
+                    &token_in_program,
+                ),
+                solver_token_in_account: get_associated_token_address_with_program_id(
+                    &solver.pubkey(),
+                    &intent.token_in,
+                    &token_in_program,
+                ),
+                token_out_mint,
+                solver_token_out_account: get_associated_token_address_with_program_id(
+                    &solver.pubkey(),
+                    &token_out_mint,
+                    &token_out_program,
+                ),
+                user_token_out_account: get_associated_token_address_with_program_id(
+                    &intent.user,
+                    &token_out_mint,
+                    &token_out_program,
+                ),
+                token_in_program,
+                token_out_program,
+            })
+            .args(mantis_escrow_program::instruction::SendFundsToUser {})
+            .instructions()
+            .map_err(|e| ChainError::Other(format!("Failed to build instruction: {}", e)))
+    }
+
+    /// Checks `intent` can be escrowed by this client and builds its `escrow_and_store_intent`
+    /// instruction. Returns the intent id along with it.
+    async fn submit_intent_instructions(&self, intent: UserIntent) -> Result<(String, Vec<Instruction>), ChainError> {
+        let user = self.sender_keypair.clone();
+
+        let domain = Domain::from(self.network);
+        if intent.src_domain != domain {
+            return Err(ChainError::DomainMismatch {
+                expected: domain,
+                actual: intent.src_domain,
+            });
+        }
+        // the escrow program can't settle cross-domain intents yet and rejects them
+        if !intent.is_single_domain() {
+            return Err(ChainError::StoreIntentError(
+                "Cross-domain intents aren't supported by the Solana escrow yet".to_string(),
+            ));
+        }
+        // the escrow program settles single domain intents to the intent's user
+        if intent.dst_user != intent.src_user {
+            return Err(ChainError::StoreIntentError(
+                "Single domain intents must pay out to the source user".to_string(),
+            ));
+        }
+
+        let program = self.program().map_err(|e| {
+            ChainError::StoreIntentError(e.to_string())
+        })?;
+
+        let intent_id = intent.intent_id();
+        let intent_state = self.intent_address(&intent_id);
+
+        let token_in_mint: Pubkey = intent.token_in.parse()?;
+        let amount = intent
+            .amount_in
+            .to_u64()
+            .map_err(|e| ChainError::StoreIntentError(format!("Invalid amount_in: {}", e)))?;
+        let token_program = self
+            .token_program(&token_in_mint)
+            .await
+            .map_err(|e| ChainError::StoreIntentError(e.to_string()))?;
+
+        let instructions = program
+            .request()
+            .accounts(mantis_escrow_program::accounts::EscrowAndStoreIntent {
+                user: user.pubkey(),
+                intent: intent_state,
+                token_in_mint,
+                user_token_account: get_associated_token_address_with_program_id(
+                    &user.pubkey(),
+                    &token_in_mint,
+                    &token_program,
+                ),
+                escrow_token_account: get_associated_token_address_with_program_id(
+                    &intent_state,
+                    &token_in_mint,
+                    &token_program,
+                ),
+                token_program,
+                associated_token_program: anchor_spl::associated_token::ID,
+                system_program: anchor_lang::system_program::ID,
+            })
+            .args(mantis_escrow_program::instruction::EscrowAndStoreIntent {
+                intent_id: intent_id.clone(),
+                amount,
+                new_intent: intent,
+            })
+            .instructions()
+            .map_err(|e| ChainError::StoreIntentError(format!("Failed to build instruction: {}", e)))?;
+
+        Ok((intent_id, instructions))
+    }
+
+    /// Checks the signer may cancel the intent, paying out on `dst_domain`, and builds its
+    /// `user_cancel_intent` instruction.
+    async fn cancel_intent_instructions(
+        &self,
+        intent_id: &str,
+        dst_domain: Domain,
+    ) -> Result<Vec<Instruction>, ChainError> {
+        let user = self.sender_keypair.clone();
+        let program = self.program()?;
+
+        let intent_state = self.intent_address(intent_id);
+        let intent = program.account::<IntentState>(intent_state).await.map_err(|e| {
+            ChainError::TransactionProcessingError(format!("Failed to get intent {}: {}", intent_id, e))
+        })?;
+        // checked by the program too, but failing here doesn't cost a fee
+        if intent.user != user.pubkey() {
+            return Err(ChainError::NotIntentUser {
+                intent_id: intent_id.to_string(),
+                src_user: intent.user.to_string(),
+            });
+        }
+        if intent.dst_domain != dst_domain.name() {
+            return Err(ChainError::Other(format!(
+                "Intent {} pays out on {}, not on {}",
+                intent_id, intent.dst_domain, dst_domain
+            )));
+        }
+        // `user_cancel_intent` always refunds directly, which is only safe for single domain intents
+        let domain = Domain::from(self.network);
+        if dst_domain != domain {
+            return Err(ChainError::Other(format!(
+                "Intent {} pays out on {}, only {} intents can be cancelled",
+                intent_id, intent.dst_domain, domain
+            )));
+        }
+        if intent.timeout >= self.clock_timestamp().await? {
+            return Err(ChainError::IntentNotExpired {
+                intent_id: intent_id.to_string(),
+                timeout: intent.timeout,
+            });
+        }
+        let token_program = self.token_program(&intent.token_in).await?;
+
+        program
+            .request()
+            .accounts(mantis_escrow_program::accounts::UserCancelIntent {
+                user: user.pubkey(),
+                intent: intent_state,
+                token_in_mint: intent.token_in,
+                escrow_token_account: get_associated_token_address_with_program_id(
+                    &intent_state,
+                    &intent.token_in,
+                    &token_program,
+                ),
+                user_token_account: get_associated_token_address_with_program_id(
+                    &user.pubkey(),
+                    &intent.token_in,
+                    &token_program,
+                ),
+                token_program,
+            })
+            .args(mantis_escrow_program::instruction::UserCancelIntent {})
+            .instructions()
+            .map_err(|e| ChainError::Other(format!("Failed to build instruction: {}", e)))
+    }
+
+    fn program(&self) -> Result<Program<Arc<Keypair>>, ChainError> {
+        let client = anchor_client::Client::new_with_options(
+            SolanaCluster::Custom(
+                self.rpc_client.url().to_string(),
+                self.rpc_client.url().to_string(),
+            ),
+            self.sender_keypair.clone(),
+            self.rpc_client.commitment(),
+        );
+
+        client.program(self.program_id).map_err(|e| {
+            ChainError::Other(format!("Failed to get program instance: {}", e))
+        })
+    }
+
+    fn intent_address(&self, intent_id: &str) -> Pubkey {
+        Pubkey::find_program_address(
+            &[mantis_escrow_program::INTENT_SEED, intent_id.as_bytes()],
+            &self.program_id,
+        )
+        .0
+    }
+
+    fn auctioneer_address(&self) -> Pubkey {
+        Pubkey::find_program_address(&[mantis_escrow_program::AUCTIONEER_SEED], &self.program_id).0
+    }
+
+    fn program_data_address(&self) -> Pubkey {
+        Pubkey::find_program_address(&[self.program_id.as_ref()], &bpf_loader_upgradeable::ID).0
+    }
+
+    /// Signs `instructions` into a transaction paid by this client's signer, sends it and
+    /// waits for it to be confirmed. Returns its signature and the slot it landed in.
+    ///
+    /// The send config's compute budget instructions are prepended to `instructions`.
+    async fn send_instructions(&self, instructions: &[Instruction]) -> Result<(Signature, u64), ChainError> {
+        let mut all_instructions = self.send_config.compute_budget_instructions();
+        all_instructions.extend_from_slice(instructions);
+        let blockhash = self
+            .rpc_client
+            .get_latest_blockhash()
+            .await
+            .map_err(|e| ChainError::TransactionProcessingError(format!("Failed to get blockhash: {}", e)))?;
+        let transaction = Transaction::new_signed_with_payer(
+            &all_instructions,
+            Some(&self.sender_keypair.pubkey()),
+            &[self.sender_keypair.as_ref()],
+            blockhash,
+        );
+        let signature = self
+            .rpc_client
+            .send_transaction_with_config(
+                &transaction,
+                RpcSendTransactionConfig {
+                    skip_preflight: self.send_config.skip_preflight,
+                    preflight_commitment: Some(self.send_config.commitment.commitment),
+                    max_retries: self.send_config.max_retries,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| {
+                // preflight failures carry the simulated transaction's error
+                match e.get_transaction_error().and_then(|err| self.escrow_error(&transaction, &err)) {
+                    Some(error) => ChainError::EscrowProgramError(error),
+                    None => ChainError::TransactionProcessingError(format!("Failed to send transaction: {}", e)),
+                }
+            })?;
+        let slot = self.confirm_transaction(&transaction).await?;
+        Ok((signature, slot))
+    }
+
+    /// Returns the escrow program error `transaction` failed with, `None` if it failed otherwise.
+    fn escrow_error(&self, transaction: &Transaction, err: &TransactionError) -> Option<EscrowError> {
+        let TransactionError::InstructionError(index, InstructionError::Custom(code)) = err else {
+            return None;
+        };
+        // other programs' custom codes may collide, only map the escrow's instructions
+        let instruction = transaction.message.instructions.get(*index as usize)?;
+        if transaction.message.account_keys.get(instruction.program_id_index as usize) != Some(&self.program_id) {
+            return None;
+        }
+        ESCROW_ERRORS.into_iter().find(|error| u32::from(*error) == *code)
+    }
+
+    /// Simulates a transaction of `instructions` with `simulateTransaction`, against the state
+    /// at the configured commitment. Compute budget instructions are prepended as when sending.
+    async fn simulate_instructions(&self, instructions: &[Instruction]) -> Result<SimulationReport, ChainError> {
+        let mut all_instructions = self.send_config.compute_budget_instructions();
+        all_instructions.extend_from_slice(instructions);
+        // the node fills in a recent blockhash, so the transaction doesn't need to be signed
+        let transaction = Transaction::new_with_payer(&all_instructions, Some(&self.sender_keypair.pubkey()));
+        let result = self
+            .rpc_client
+            .simulate_transaction_with_config(
+                &transaction,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    commitment: Some(self.send_config.commitment),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| ChainError::TransactionProcessingError(format!("Failed to simulate transaction: {}", e)))?
+            .value;
+
+        Ok(SimulationReport {
+            success: result.err.is_none(),
+            error: result.err.map(|err| match self.escrow_error(&transaction, &err) {
+                Some(error) => error.to_string(),
+                None => err.to_string(),
+            }),
+            gas_used: result.units_consumed,
+            logs: result.logs.unwrap_or_default(),
+            approval: None,
+            escrow_call_verified: true,
+        })
+    }
+
+    /// Polls the status of the sent `transaction` until it reaches the configured commitment,
+    /// returning the slot it landed in.
+    async fn confirm_transaction(&self, transaction: &Transaction) -> Result<u64, ChainError> {
+        let signature = &transaction.signatures[0];
+        let started = Instant::now();
+        loop {
+            let status = self
+                .rpc_client
+                .get_signature_statuses(&[*signature])
+                .await
+                .map_err(|e| ChainError::TransactionInfoError(e.to_string()))?
+                .value
+                .into_iter()
+                .flatten()
+                .next();
+            if let Some(status) = status {
+                if let Some(err) = status.err {
+                    return Err(match self.escrow_error(transaction, &err) {
+                        Some(error) => ChainError::EscrowProgramError(error),
+                        None => ChainError::TransactionProcessingError(format!(
+                            "Transaction {} failed: {}",
+                            signature, err
+                        )),
+                    });
+                }
+                if status.satisfies_commitment(self.send_config.commitment) {
+                    return Ok(status.slot);
+                }
+            }
+            if started.elapsed() >= self.confirmation.timeout {
+                return Err(ChainError::ConfirmationTimeout(signature.to_string()));
+            }
+            tokio::time::sleep(self.confirmation.poll_interval).await;
         }
     }
+
+    /// Returns the owner of `mint`, telling whether it's an SPL Token or a Token-2022 mint.
+    async fn token_program(&self, mint: &Pubkey) -> Result<Pubkey, ChainError> {
+        self.rpc_client
+            .get_account(mint)
+            .await
+            .map(|account| account.owner)
+            .map_err(|e| ChainError::Other(format!("Failed to get mint {}: {}", mint, e)))
+    }
+
+    /// Returns the cluster's unix timestamp, which the escrow program checks timeouts against.
+    async fn clock_timestamp(&self) -> Result<u64, ChainError> {
+        let account = self
+            .rpc_client
+            .get_account(&sysvar::clock::ID)
+            .await
+            .map_err(|e| ChainError::Other(format!("Failed to get clock: {}", e)))?;
+        let clock: Clock = from_account(&account)
+            .ok_or_else(|| ChainError::Other("Failed to decode clock".to_string()))?;
+        Ok(clock.unix_timestamp as u64)
+    }
+
+    /// Decodes a top level escrow program instruction, ignoring other programs.
+    fn decode_instruction(&self, instruction: &UiInstruction) -> Option<EscrowCall> {
+        let UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(instruction)) = instruction else {
+            return None;
+        };
+        if instruction.program_id != self.program_id.to_string() {
+            return None;
+        }
+        let data = bs58::decode(&instruction.data).into_vec().ok()?;
+        if data.len() < 8 {
+            return None;
+        }
+        let (discriminator, mut args) = data.split_at(8);
+
+        let call = if discriminator == escrow_instruction::EscrowAndStoreIntent::DISCRIMINATOR {
+            let ix = escrow_instruction::EscrowAndStoreIntent::deserialize(&mut args).ok()?;
+            EscrowCall::EscrowFunds {
+                intent_id: Some(ix.intent_id),
+                user: ix.new_intent.src_user,
+                token_in: ix.new_intent.token_in,
+                amount_in: ix.new_intent.amount_in.raw().to_string(),
+                token_out: ix.new_intent.token_out,
+                amount_out: ix.new_intent.amount_out.raw().to_string(),
+            }
+        } else if discriminator == escrow_instruction::InitializeAuctioneer::DISCRIMINATOR {
+            EscrowCall::InitializeAuctioneer
+        } else if discriminator == escrow_instruction::UpdateAuctionData::DISCRIMINATOR {
+            let ix = escrow_instruction::UpdateAuctionData::deserialize(&mut args).ok()?;
+            EscrowCall::UpdateAuctionData {
+                intent_id: ix._intent_id,
+                amount_out: ix.amount_out.to_string(),
+                winner_solver: ix.winner_solver,
+            }
+        } else if discriminator == escrow_instruction::SendFundsToUser::DISCRIMINATOR {
+            // only the intent account is passed, the id lives in its (by now closed) state
+            EscrowCall::SendFundsToUser { intent_id: None }
+        } else if discriminator == escrow_instruction::UserCancelIntent::DISCRIMINATOR {
+            EscrowCall::UserCancelIntent { intent_id: None }
+        } else {
+            return None;
+        };
+        Some(call)
+    }
 }
 
 #[async_trait]
 impl Chain for SolanaClient {
-    type Transaction = ();
+    type Transaction = TransactionSummary;
     type Address = Pubkey;
     type Token = ();
     type Amount = ();
     type Error = ChainError;
 
-    async fn get_transaction(&self, _tx_hash: &str) -> Result<Self::Transaction, Self::Error> {
-        unimplemented!("get_transaction not implemented for SolanaClient")
+    async fn get_transaction(&self, tx_hash: &str) -> Result<Self::Transaction, Self::Error> {
+        let signature: Signature = tx_hash.parse().map_err(|e| {
+            ChainError::TransactionInfoError(format!("Invalid signature {}: {}", tx_hash, e))
+        })?;
+        let tx = self
+            .rpc_client
+            .get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::JsonParsed),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await
+            .map_err(|e| ChainError::TransactionInfoError(e.to_string()))?;
+
+        let meta = tx.transaction.meta;
+        let events = match &meta {
+            Some(meta) if meta.err.is_none() => {
+                let logs: Option<Vec<String>> = meta.log_messages.clone().into();
+                decode_logs(&self.program_id, &logs.unwrap_or_default())
+            }
+            _ => Vec::new(),
+        };
+        let status = match &meta {
+            Some(meta) if meta.err.is_some() => TransactionStatus::Failed,
+            Some(_) => TransactionStatus::Success,
+            None => TransactionStatus::Unknown,
+        };
+        let calls = match tx.transaction.transaction {
+            EncodedTransaction::Json(ui_transaction) => match ui_transaction.message {
+                UiMessage::Parsed(message) => message
+                    .instructions
+                    .iter()
+                    .filter_map(|instruction| self.decode_instruction(instruction))
+                    .collect(),
+                UiMessage::Raw(_) => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+
+        Ok(TransactionSummary {
+            tx_hash: signature.to_string(),
+            status,
+            fee: meta.map(|meta| meta.fee as u128),
+            block_number: Some(tx.slot),
+            calls,
+            events,
+        })
+    }
+
+    async fn get_current_block_number(&self) -> Result<u64, Self::Error> {
+        self.rpc_client
+            .get_slot()
+            .await
+            .map_err(|e| ChainError::BlockNumberError(e.to_string()))
+    }
+
+    async fn token_decimals(&self, token: &str) -> Result<u8, Self::Error> {
+        let mint: Pubkey = token.parse()?;
+        let account = self
+            .rpc_client
+            .get_account(&mint)
+            .await
+            .map_err(|e| ChainError::Other(format!("Failed to get mint {}: {}", mint, e)))?;
+        let mint = Mint::try_deserialize(&mut account.data.as_slice())
+            .map_err(|e| ChainError::Other(format!("Failed to decode mint {}: {}", mint, e)))?;
+        Ok(mint.decimals)
     }
 
     async fn submit_intent(
         &self,
         intent: UserIntent,
-        program_id: Self::Address,
-    ) -> Result<(), Self::Error> {
-        let auctioneer = self.sender_keypair.clone();
-        let _auctioneer_state = Pubkey::find_program_address(&[b"auctioneer"], &program_id).0;
-
-        let client = anchor_client::Client::new_with_options(
-            SolanaCluster::Custom(
-                self.rpc_client.url().to_string(),
-                self.rpc_client.url().to_string(),
-            ),
-            auctioneer.clone(),
-            CommitmentConfig::processed(),
-        );
+    ) -> Result<SubmitReceipt, Self::Error> {
+        let (intent_id, instructions) = self.submit_intent_instructions(intent).await?;
+        let (signature, slot) = self.send_instructions(&instructions).await?;
 
-        let program = client.program(program_id).map_err(|e| {
-            ChainError::StoreIntentError(format!("Failed to get program instance: {}", e))
+        // read the id back from the intent account to make sure it was stored
+        let intent = self.get_intent(&intent_id).await?.ok_or_else(|| {
+            let intent_state = self.intent_address(&intent_id);
+            ChainError::StoreIntentError(format!("Intent account {} not found", intent_state))
         })?;
 
-        let intent_id = b"123"; // TODO
-        let _intent_state = Pubkey::find_program_address(&[b"intent", intent_id], &program.id()).0;
+        Ok(SubmitReceipt {
+            tx_hash: signature.to_string(),
+            block_number: Some(slot),
+            intent_id: intent.intent_id,
+        })
+    }
 
-        let result = program
-            .request()
-            .accounts(mantis_escrow_program::accounts::Initialize {})
-            .args(mantis_escrow_program::instruction::EscrowAndStoreIntent {
-                _amount: 0,
-                _new_intent: intent,
-            })
-            .payer(auctioneer.clone())
-            .signer(auctioneer.as_ref())
-            .send_with_spinner_and_config(RpcSendTransactionConfig {
-                skip_preflight: true,
-                ..Default::default()
-            });
+    async fn simulate_submit_intent(&self, intent: UserIntent) -> Result<SimulationReport, Self::Error> {
+        let (_, instructions) = self.submit_intent_instructions(intent).await?;
+        self.simulate_instructions(&instructions).await
+    }
+
+    async fn get_intent(
+        &self,
+        intent_id: &str,
+    ) -> Result<Option<Intent>, Self::Error> {
+        let intent_state = self.intent_address(intent_id);
+        let Some(account) = self
+            .rpc_client
+            .get_account_with_commitment(&intent_state, self.rpc_client.commitment())
+            .await
+            .map_err(|e| ChainError::Other(format!("Failed to get intent {}: {}", intent_id, e)))?
+            .value
+        else {
+            return Ok(None);
+        };
+        let intent = IntentState::try_deserialize(&mut account.data.as_slice())
+            .map_err(|e| ChainError::Other(format!("Failed to decode intent {}: {}", intent_id, e)))?;
+        let decimals_in = self.token_decimals(&intent.token_in.to_string()).await?;
+        let src_domain = Domain::from(self.network);
+        let dst_domain = intent.dst_domain.parse::<Domain>().map_err(ChainError::Other)?;
+        // the decimals of tokens on other chains aren't known here, keep those in base units
+        let decimals_out = if dst_domain == src_domain {
+            self.token_decimals(&intent.token_out).await?
+        } else {
+            0
+        };
+
+        Ok(Some(Intent {
+            intent_id: intent.intent_id,
+            src_domain,
+            dst_domain: Some(dst_domain),
+            src_user: intent.user.to_string(),
+            dst_user: intent.dst_user,
+            token_in: intent.token_in.to_string(),
+            amount_in: TokenAmount::new(U256::from(intent.amount_in), decimals_in),
+            token_out: intent.token_out,
+            amount_out: TokenAmount::new(U256::from(intent.amount_out), decimals_out),
+            winner_solver: (!intent.winner_solver.is_empty()).then_some(intent.winner_solver),
+            timeout: intent.timeout,
+        }))
+    }
+
+    async fn intent_status(&self, intent_id: &str) -> Result<IntentStatus, Self::Error> {
+        if let Some(intent) = self.get_intent(intent_id).await? {
+            return Ok(intent.status(self.clock_timestamp().await?));
+        }
+
+        // the intent account is closed by both settlement and cancellation,
+        // the transaction that closed it tells which
+        let signatures = self
+            .rpc_client
+            .get_signatures_for_address(&self.intent_address(intent_id))
+            .await
+            .map_err(|e| ChainError::TransactionInfoError(e.to_string()))?;
+        for signature in signatures.iter().filter(|signature| signature.err.is_none()) {
+            let tx = self.get_transaction(&signature.signature).await?;
+            for call in &tx.calls {
+                match call {
+                    EscrowCall::SendFundsToUser { .. } => return Ok(IntentStatus::Settled),
+                    EscrowCall::UserCancelIntent { .. } => return Ok(IntentStatus::Cancelled),
+                    _ => {}
+                }
+            }
+        }
+        Err(ChainError::IntentNotFound(intent_id.to_string()))
+    }
+
+    async fn cancel_intent(&self, intent_id: &str, dst_domain: Domain) -> Result<CancelReceipt, Self::Error> {
+        let instructions = self.cancel_intent_instructions(intent_id, dst_domain).await?;
+        let (signature, _) = self.send_instructions(&instructions).await?;
+        // only single domain intents get this far, the program refunds those directly
+        Ok(CancelReceipt {
+            tx_hash: signature.to_string(),
+            outcome: CancelOutcome::Refunded,
+        })
+    }
 
-        result
-            .map_err(|e| ChainError::StoreIntentError(format!("Failed to send transaction: {}", e)))
-            .map(|_| ())
+    async fn simulate_cancel_intent(
+        &self,
+        intent_id: &str,
+        dst_domain: Domain,
+    ) -> Result<SimulationReport, Self::Error> {
+        let instructions = self.cancel_intent_instructions(intent_id, dst_domain).await?;
+        self.simulate_instructions(&instructions).await
+    }
+
+    async fn settle_intent(&self, intent_id: &str) -> Result<SettleReceipt, Self::Error> {
+        self.send_funds_to_user(intent_id).await
+    }
+
+    async fn simulate_settle_intent(&self, intent_id: &str) -> Result<SimulationReport, Self::Error> {
+        let instructions = self.send_funds_to_user_instructions(intent_id).await?;
+        self.simulate_instructions(&instructions).await
     }
 
     fn signer(&self) -> Self::Address {
         self.sender_keypair.pubkey()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escrow_errors_are_complete_and_in_order() {
+        let source = include_str!("../../../contracts/solana/escrow/src/lib.rs");
+        let start = source.find("pub enum EscrowError {").unwrap();
+        let end = start + source[start..].find("\n}").unwrap();
+        let declared: Vec<&str> = source[start..end]
+            .lines()
+            .skip(1)
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("#[") && !line.starts_with("//"))
+            .map(|line| line.trim_end_matches(','))
+            .collect();
+        let listed: Vec<String> = ESCROW_ERRORS.iter().map(|error| format!("{:?}", error)).collect();
+        assert_eq!(listed, declared);
+        // anchor numbers custom errors from 6000 in declaration order
+        for (index, error) in ESCROW_ERRORS.iter().enumerate() {
+            assert_eq!(u32::from(*error), 6000 + index as u32);
+        }
+    }
+}
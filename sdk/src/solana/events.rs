@@ -0,0 +1,415 @@
+use crate::events::{EscrowEvent, EscrowEventLog};
+use crate::solana::{read_commitment, ChainError};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::Engine;
+use futures::{Stream, StreamExt};
+use mantis_escrow_program::{AuctionUpdated, IntentCancelled, IntentEscrowed, IntentSettled};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Largest page `getSignaturesForAddress` returns.
+const SIGNATURES_PAGE_SIZE: usize = 1000;
+
+/// Options of [`SolanaClient::escrow_events`](crate::solana::SolanaClient::escrow_events).
+#[derive(Clone, Debug)]
+pub struct EventStreamConfig {
+    /// Commitment transactions must reach before their events are yielded. Polling needs
+    /// `confirmed` at least, so `processed` only applies to the websocket subscription.
+    pub commitment: CommitmentConfig,
+    /// How often to check for new transactions when the websocket endpoint isn't available.
+    pub poll_interval: Duration,
+    /// Replay the events of transactions after this one, defaults to only new transactions.
+    pub from_signature: Option<Signature>,
+}
+
+impl Default for EventStreamConfig {
+    fn default() -> Self {
+        Self {
+            commitment: CommitmentConfig::confirmed(),
+            poll_interval: Duration::from_secs(2),
+            from_signature: None,
+        }
+    }
+}
+
+/// Decodes the events `program_id` emitted in a transaction's `logs`.
+///
+/// Anchor logs events as base64 `Program data:` lines. The invocation stack is tracked
+/// so that lines logged by other programs, which could fake the discriminators, are skipped.
+pub(crate) fn decode_logs(program_id: &Pubkey, logs: &[String]) -> Vec<EscrowEvent> {
+    let program_id = program_id.to_string();
+    let mut invocations: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for log in logs {
+        let Some(log) = log.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = log.strip_prefix("data: ") {
+            if invocations.last() == Some(&program_id.as_str()) {
+                events.extend(decode_event(data));
+            }
+        } else if let Some((program, rest)) = log.split_once(' ') {
+            if rest.starts_with("invoke [") {
+                invocations.push(program);
+            } else if rest == "success" || rest.starts_with("failed") {
+                invocations.pop();
+            }
+        }
+    }
+    events
+}
+
+fn decode_event(data: &str) -> Option<EscrowEvent> {
+    let data = base64::engine::general_purpose::STANDARD.decode(data).ok()?;
+    if data.len() < 8 {
+        return None;
+    }
+    let (discriminator, mut args) = data.split_at(8);
+
+    let event = if discriminator == IntentEscrowed::DISCRIMINATOR {
+        let event = IntentEscrowed::deserialize(&mut args).ok()?;
+        EscrowEvent::FundsEscrowed {
+            intent_id: event.intent_id,
+        }
+    } else if discriminator == AuctionUpdated::DISCRIMINATOR {
+        let event = AuctionUpdated::deserialize(&mut args).ok()?;
+        EscrowEvent::AuctionUpdated {
+            intent_id: event.intent_id,
+            amount_out: event.amount_out.to_string(),
+            winner_solver: event.winner_solver,
+        }
+    } else if discriminator == IntentSettled::DISCRIMINATOR {
+        let event = IntentSettled::deserialize(&mut args).ok()?;
+        EscrowEvent::IntentSettled {
+            intent_id: event.intent_id,
+            solver: event.solver.to_string(),
+            amount_out: event.amount_out.to_string(),
+        }
+    } else if discriminator == IntentCancelled::DISCRIMINATOR {
+        let event = IntentCancelled::deserialize(&mut args).ok()?;
+        EscrowEvent::IntentCancelled {
+            intent_id: event.intent_id,
+            user: event.user.to_string(),
+        }
+    } else {
+        return None;
+    };
+    Some(event)
+}
+
+/// Spawns a task following the escrow program's events and returns the stream it feeds.
+///
+/// Transactions are received over `logsSubscribe`. Until the subscription is up and
+/// after it drops, the program's transactions are polled with `getSignaturesForAddress`
+/// starting from the last one seen, so no events are lost in between.
+pub(super) fn spawn_event_stream(
+    rpc_url: String,
+    ws_url: String,
+    program_id: Pubkey,
+    config: EventStreamConfig,
+) -> impl Stream<Item = Result<EscrowEventLog, ChainError>> {
+    let (sender, receiver) = mpsc::channel(64);
+    tokio::spawn(async move {
+        let mut follower = EventFollower {
+            rpc_client: RpcClient::new_with_commitment(rpc_url, read_commitment(config.commitment)),
+            program_id,
+            commitment: read_commitment(config.commitment),
+            last_signature: config.from_signature,
+            sender,
+        };
+        if config.from_signature.is_none() && !follower.start(config.poll_interval).await {
+            return;
+        }
+
+        if let Ok(pubsub) = PubsubClient::new(&ws_url).await {
+            let subscription = pubsub
+                .logs_subscribe(
+                    RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]),
+                    RpcTransactionLogsConfig {
+                        commitment: Some(config.commitment),
+                    },
+                )
+                .await;
+            if let Ok((mut logs, _unsubscribe)) = subscription {
+                // catch up on what happened before subscribing, the subscription
+                // may deliver some of those transactions again
+                let Some(caught_up) = follower.poll().await else {
+                    return;
+                };
+                let mut seen: HashSet<String> = caught_up.into_iter().collect();
+                while let Some(response) = logs.next().await {
+                    let logs = response.value;
+                    if logs.err.is_some() || seen.remove(&logs.signature) {
+                        continue;
+                    }
+                    if let Ok(signature) = logs.signature.parse() {
+                        follower.last_signature = Some(signature);
+                    }
+                    let events = decode_logs(&program_id, &logs.logs);
+                    if !follower.send(events, &logs.signature, response.context.slot).await {
+                        return;
+                    }
+                }
+            }
+        }
+
+        // the websocket isn't available or dropped, keep going over HTTP
+        loop {
+            if follower.poll().await.is_none() {
+                return;
+            }
+            tokio::time::sleep(config.poll_interval).await;
+        }
+    });
+
+    futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|item| (item, receiver))
+    })
+}
+
+struct EventFollower {
+    rpc_client: RpcClient,
+    program_id: Pubkey,
+    commitment: CommitmentConfig,
+    /// Newest transaction whose events were yielded, `None` to yield all of the program's
+    /// transactions, e.g. when it had none yet when the stream started.
+    last_signature: Option<Signature>,
+    sender: mpsc::Sender<Result<EscrowEventLog, ChainError>>,
+}
+
+impl EventFollower {
+    /// Starts after the program's newest transaction, so that only later ones are yielded.
+    ///
+    /// Retries every `retry_interval` until the RPC node answers, returns `false` once the
+    /// stream is dropped.
+    async fn start(&mut self, retry_interval: Duration) -> bool {
+        loop {
+            match self.signatures(None, Some(1)).await {
+                Ok(signatures) => {
+                    self.last_signature =
+                        signatures.first().and_then(|(signature, _, _)| signature.parse().ok());
+                    return true;
+                }
+                Err(err) => {
+                    if self.sender.send(Err(err)).await.is_err() {
+                        return false;
+                    }
+                }
+            }
+            tokio::time::sleep(retry_interval).await;
+        }
+    }
+
+    /// Yields the events of the program's transactions since the last one seen.
+    ///
+    /// Returns the signatures of the yielded transactions, `None` once the stream is dropped.
+    async fn poll(&mut self) -> Option<Vec<String>> {
+        let signatures = match self.signatures(self.last_signature, None).await {
+            Ok(signatures) => signatures,
+            Err(err) => return self.sender.send(Err(err)).await.ok().map(|_| Vec::new()),
+        };
+        let mut yielded = Vec::new();
+        // signatures come newest first
+        for (signature, slot, failed) in signatures.into_iter().rev() {
+            if !failed {
+                let events = match self.transaction_events(&signature).await {
+                    Ok(events) => events,
+                    // retry from here on the next poll
+                    Err(err) => return self.sender.send(Err(err)).await.ok().map(|_| yielded),
+                };
+                if !self.send(events, &signature, slot).await {
+                    return None;
+                }
+                yielded.push(signature.clone());
+            }
+            if let Ok(signature) = signature.parse() {
+                self.last_signature = Some(signature);
+            }
+        }
+        Some(yielded)
+    }
+
+    /// Returns the program's transactions after `until`, newest first, as
+    /// `(signature, slot, failed)`.
+    async fn signatures(
+        &self,
+        until: Option<Signature>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(String, u64, bool)>, ChainError> {
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self
+                .rpc_client
+                .get_signatures_for_address_with_config(
+                    &self.program_id,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit,
+                        commitment: Some(self.commitment),
+                    },
+                )
+                .await
+                .map_err(|e| ChainError::TransactionInfoError(e.to_string()))?;
+            let done = limit.is_some() || page.len() < SIGNATURES_PAGE_SIZE;
+            before = page.last().and_then(|status| status.signature.parse().ok());
+            signatures.extend(
+                page.into_iter()
+                    .map(|status| (status.signature, status.slot, status.err.is_some())),
+            );
+            if done || before.is_none() {
+                return Ok(signatures);
+            }
+        }
+    }
+
+    async fn transaction_events(&self, signature: &str) -> Result<Vec<EscrowEvent>, ChainError> {
+        let signature: Signature = signature.parse().map_err(|e| {
+            ChainError::TransactionInfoError(format!("Invalid signature {}: {}", signature, e))
+        })?;
+        let tx = self
+            .rpc_client
+            .get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Json),
+                    commitment: Some(self.commitment),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await
+            .map_err(|e| ChainError::TransactionInfoError(e.to_string()))?;
+        let logs: Option<Vec<String>> = tx
+            .transaction
+            .meta
+            .and_then(|meta| meta.log_messages.into());
+        Ok(decode_logs(&self.program_id, &logs.unwrap_or_default()))
+    }
+
+    /// Yields `events` of transaction `signature`, returns `false` once the stream is dropped.
+    async fn send(&self, events: Vec<EscrowEvent>, signature: &str, slot: u64) -> bool {
+        for event in events {
+            let event = EscrowEventLog {
+                event,
+                tx_hash: signature.to_string(),
+                block_number: slot,
+            };
+            if self.sender.send(Ok(event)).await.is_err() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+
+    fn data_line(event: &impl Event) -> String {
+        format!("Program data: {}", base64::engine::general_purpose::STANDARD.encode(event.data()))
+    }
+
+    fn cancelled() -> IntentCancelled {
+        IntentCancelled {
+            intent_id: "intent".to_string(),
+            user: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn decodes_events_of_the_program() {
+        let program_id = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            "Program log: Instruction: UpdateAuctionData".to_string(),
+            data_line(&AuctionUpdated {
+                intent_id: "intent".to_string(),
+                amount_out: 42,
+                winner_solver: "solver".to_string(),
+            }),
+            data_line(&IntentCancelled {
+                intent_id: "intent".to_string(),
+                user,
+            }),
+            format!("Program {} success", program_id),
+        ];
+        assert_eq!(
+            decode_logs(&program_id, &logs),
+            vec![
+                EscrowEvent::AuctionUpdated {
+                    intent_id: "intent".to_string(),
+                    amount_out: "42".to_string(),
+                    winner_solver: "solver".to_string(),
+                },
+                EscrowEvent::IntentCancelled {
+                    intent_id: "intent".to_string(),
+                    user: user.to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_events_logged_by_other_programs() {
+        let program_id = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        // another program fakes an escrow event, directly or from a CPI made by the escrow
+        let logs = vec![
+            format!("Program {} invoke [1]", other),
+            data_line(&cancelled()),
+            format!("Program {} invoke [2]", program_id),
+            format!("Program {} success", program_id),
+            data_line(&cancelled()),
+            format!("Program {} success", other),
+            format!("Program {} invoke [1]", program_id),
+            format!("Program {} invoke [2]", other),
+            data_line(&cancelled()),
+            format!("Program {} success", other),
+            format!("Program {} success", program_id),
+        ];
+        assert!(decode_logs(&program_id, &logs).is_empty());
+    }
+
+    #[test]
+    fn tracks_failed_invocations() {
+        let program_id = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            format!("Program {} invoke [2]", other),
+            format!("Program {} failed: custom program error: 0x1", other),
+            data_line(&cancelled()),
+            format!("Program {} success", program_id),
+        ];
+        assert_eq!(decode_logs(&program_id, &logs).len(), 1);
+    }
+
+    #[test]
+    fn ignores_malformed_data() {
+        let program_id = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            "Program data: not base64!".to_string(),
+            "Program data: AAAA".to_string(),
+            format!(
+                "Program data: {}",
+                base64::engine::general_purpose::STANDARD.encode([0u8; 16])
+            ),
+            format!("Program {} success", program_id),
+        ];
+        assert!(decode_logs(&program_id, &logs).is_empty());
+    }
+}
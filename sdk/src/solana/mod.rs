@@ -1,9 +1,16 @@
 use anchor_lang::prelude::thiserror::Error;
-use anchor_lang::solana_program::pubkey::ParsePubkeyError;
+use anchor_lang::solana_program::pubkey::{ParsePubkeyError, Pubkey};
 use clap::ValueEnum;
+use mantis_common::{Domain, MantisNetwork, SolanaCluster};
+use mantis_escrow_program::EscrowError;
+use solana_sdk::commitment_config::CommitmentConfig;
 
 pub(crate) mod cli;
 pub(crate) mod client;
+mod events;
+
+pub use client::{ConfirmationConfig, SolanaClient, SolanaSendConfig};
+pub use events::EventStreamConfig;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
 #[clap(rename_all = "kebab-case")]
@@ -36,6 +43,34 @@ impl Cluster {
             ),
         }
     }
+
+    /// Returns the id of the escrow program deployed on the cluster.
+    fn default_escrow_program_id(&self) -> Pubkey {
+        mantis_escrow_program::ID
+    }
+}
+
+impl From<Cluster> for Domain {
+    fn from(cluster: Cluster) -> Self {
+        match cluster {
+            Cluster::SolanaMainnet => Domain::Solana(SolanaCluster::Mainnet),
+            Cluster::SolanaTestnet => Domain::Solana(SolanaCluster::Testnet),
+            Cluster::MantisMainnet => Domain::Mantis(MantisNetwork::Mainnet),
+            Cluster::MantisTestnet => Domain::Mantis(MantisNetwork::Testnet),
+        }
+    }
+}
+
+/// Returns the commitment to read at for transactions sent at `commitment`.
+///
+/// `getSignaturesForAddress` and `getTransaction` reject `processed`, so reads happen at
+/// `confirmed` at least.
+fn read_commitment(commitment: CommitmentConfig) -> CommitmentConfig {
+    if commitment.is_at_least_confirmed() {
+        commitment
+    } else {
+        CommitmentConfig::confirmed()
+    }
 }
 
 #[allow(unused)]
@@ -63,6 +98,30 @@ pub enum ChainError {
     ParsePubkeyError(#[from] ParsePubkeyError),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("Escrow program error: {0}")]
+    EscrowProgramError(EscrowError),
+    #[error("Transaction {0} wasn't confirmed in time")]
+    ConfirmationTimeout(String),
+    #[error("Intent {0} not found")]
+    IntentNotFound(String),
+    #[error("Intent {intent_id} belongs to {src_user}, not to the signer")]
+    NotIntentUser { intent_id: String, src_user: String },
+    #[error("Intent {intent_id} can't be cancelled before its timeout {timeout}")]
+    IntentNotExpired { intent_id: String, timeout: u64 },
+    #[error("Intent source domain {actual} doesn't match the client's {expected}")]
+    DomainMismatch { expected: Domain, actual: Domain },
     #[error("Other error: {0}")]
     Other(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_at_confirmed_commitment_at_least() {
+        assert_eq!(read_commitment(CommitmentConfig::processed()), CommitmentConfig::confirmed());
+        assert_eq!(read_commitment(CommitmentConfig::confirmed()), CommitmentConfig::confirmed());
+        assert_eq!(read_commitment(CommitmentConfig::finalized()), CommitmentConfig::finalized());
+    }
+}
@@ -0,0 +1,375 @@
+use alloy::primitives::Address;
+use anchor_lang::prelude::thiserror::Error;
+use anchor_lang::prelude::Pubkey;
+use mantis_common::{Domain, EthereumNetwork, MantisNetwork, SolanaCluster};
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// A token deployment known to the registry.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct TokenInfo {
+    pub symbol: String,
+    /// Domain the token is deployed on, by its name such as `ethereum-mainnet`.
+    #[serde(deserialize_with = "deserialize_domain")]
+    pub chain: Domain,
+    /// ERC-20 address on Ethereum, mint on SVM chains.
+    pub address: String,
+    pub decimals: u8,
+    /// Denomination of the token when bridged to other domains, e.g. an IBC denom.
+    #[serde(default)]
+    pub denom: Option<String>,
+}
+
+impl TokenInfo {
+    /// Returns how intents from other domains refer to this token: its `denom` if set,
+    /// otherwise its address as the escrows report it in bridge messages.
+    pub fn cross_chain_denom(&self) -> String {
+        self.denom
+            .clone()
+            .unwrap_or_else(|| address_denom(self.chain, &self.address))
+    }
+}
+
+/// The Ethereum escrow formats token addresses as lowercase hex in bridge messages,
+/// mints are base58 on every SVM chain.
+fn address_denom(chain: Domain, address: &str) -> String {
+    if chain.is_svm() {
+        address.to_string()
+    } else {
+        address.to_ascii_lowercase()
+    }
+}
+
+fn deserialize_domain<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Domain, D::Error> {
+    String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+}
+
+#[derive(Error, Debug)]
+pub enum TokenRegistryError {
+    #[error("Failed to read token registry: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to parse TOML token registry: {0}")]
+    TomlError(#[from] toml::de::Error),
+    #[error("Failed to parse JSON token registry: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Unsupported token registry format {0:?}, expected .toml or .json")]
+    UnsupportedFormat(PathBuf),
+    #[error("Invalid address {address} for {symbol} on {chain}")]
+    InvalidAddress {
+        symbol: String,
+        chain: Domain,
+        address: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct RegistryFile {
+    tokens: Vec<TokenInfo>,
+}
+
+/// Maps token symbols to their per-chain addresses, mints and decimals.
+///
+/// [`TokenRegistry::default`] holds the built-in tokens, which can be extended or
+/// overridden with a TOML or JSON file listing `tokens`:
+///
+/// ```toml
+/// [[tokens]]
+/// symbol = "USDC"
+/// chain = "ethereum-sepolia"
+/// address = "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238"
+/// decimals = 6
+/// ```
+///
+/// Built-in tokens carry the denom the escrows know them by on other domains, see
+/// [`TokenInfo::cross_chain_denom`].
+#[derive(Clone, Debug)]
+pub struct TokenRegistry {
+    tokens: Vec<TokenInfo>,
+}
+
+impl Default for TokenRegistry {
+    fn default() -> Self {
+        let tokens = BUILTIN_TOKENS
+            .iter()
+            .map(|&(symbol, chain, address, decimals)| TokenInfo {
+                symbol: symbol.to_string(),
+                chain,
+                address: address.to_string(),
+                decimals,
+                denom: Some(address_denom(chain, address)),
+            })
+            .collect();
+        Self { tokens }
+    }
+}
+
+impl TokenRegistry {
+    /// Creates a registry without any tokens.
+    pub fn empty() -> Self {
+        Self { tokens: Vec::new() }
+    }
+
+    /// Loads a registry from a `.toml` or `.json` file.
+    pub fn from_file(path: &Path) -> Result<Self, TokenRegistryError> {
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml_str(&contents),
+            Some("json") => Self::from_json_str(&contents),
+            _ => Err(TokenRegistryError::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+
+    pub fn from_toml_str(contents: &str) -> Result<Self, TokenRegistryError> {
+        Self::from_tokens(toml::from_str::<RegistryFile>(contents)?.tokens)
+    }
+
+    pub fn from_json_str(contents: &str) -> Result<Self, TokenRegistryError> {
+        Self::from_tokens(serde_json::from_str::<RegistryFile>(contents)?.tokens)
+    }
+
+    fn from_tokens(tokens: Vec<TokenInfo>) -> Result<Self, TokenRegistryError> {
+        for token in &tokens {
+            let valid = if token.chain.is_svm() {
+                token.address.parse::<Pubkey>().is_ok()
+            } else {
+                token.address.parse::<Address>().is_ok()
+            };
+            if !valid {
+                return Err(TokenRegistryError::InvalidAddress {
+                    symbol: token.symbol.clone(),
+                    chain: token.chain,
+                    address: token.address.clone(),
+                });
+            }
+        }
+        Ok(Self { tokens })
+    }
+
+    /// Adds the tokens of `other`, replacing entries with the same symbol and chain.
+    pub fn extend(&mut self, other: TokenRegistry) {
+        for token in other.tokens {
+            self.tokens.retain(|known| {
+                !(known.chain == token.chain && known.symbol.eq_ignore_ascii_case(&token.symbol))
+            });
+            self.tokens.push(token);
+        }
+    }
+
+    /// Looks up `symbol` on `chain`, ignoring case.
+    pub fn get(&self, symbol: &str, chain: Domain) -> Option<&TokenInfo> {
+        self.tokens
+            .iter()
+            .find(|token| token.chain == chain && token.symbol.eq_ignore_ascii_case(symbol))
+    }
+
+    pub fn tokens(&self) -> impl Iterator<Item = &TokenInfo> {
+        self.tokens.iter()
+    }
+}
+
+const ETHEREUM_MAINNET: Domain = Domain::Ethereum(EthereumNetwork::Mainnet);
+const ETHEREUM_SEPOLIA: Domain = Domain::Ethereum(EthereumNetwork::Sepolia);
+const SOLANA_MAINNET: Domain = Domain::Solana(SolanaCluster::Mainnet);
+const SOLANA_TESTNET: Domain = Domain::Solana(SolanaCluster::Testnet);
+const MANTIS_MAINNET: Domain = Domain::Mantis(MantisNetwork::Mainnet);
+const MANTIS_TESTNET: Domain = Domain::Mantis(MantisNetwork::Testnet);
+
+const BUILTIN_TOKENS: &[(&str, Domain, &str, u8)] = &[
+    ("USDC", ETHEREUM_MAINNET, "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", 6),
+    ("USDT", ETHEREUM_MAINNET, "0xdAC17F958D2ee523a2206206994597C13D831ec7", 6),
+    ("DAI", ETHEREUM_MAINNET, "0x6B175474E89094C44Da98b954EedeAC495271d0F", 18),
+    ("WETH", ETHEREUM_MAINNET, "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", 18),
+    // the escrow only holds ERC-20s, so ETH is escrowed as WETH
+    ("ETH", ETHEREUM_MAINNET, "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", 18),
+    ("USDC", ETHEREUM_SEPOLIA, "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238", 6),
+    ("WETH", ETHEREUM_SEPOLIA, "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14", 18),
+    ("ETH", ETHEREUM_SEPOLIA, "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14", 18),
+    ("USDC", SOLANA_MAINNET, "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", 6),
+    ("USDT", SOLANA_MAINNET, "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", 6),
+    // Wormhole wrapped ether
+    ("ETH", SOLANA_MAINNET, "7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs", 8),
+    // wrapped SOL uses the native mint on every SVM chain
+    ("SOL", SOLANA_MAINNET, "So11111111111111111111111111111111111111112", 9),
+    ("SOL", SOLANA_TESTNET, "So11111111111111111111111111111111111111112", 9),
+    ("SOL", MANTIS_MAINNET, "So11111111111111111111111111111111111111112", 9),
+    ("SOL", MANTIS_TESTNET, "So11111111111111111111111111111111111111112", 9),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+[[tokens]]
+symbol = "USDC"
+chain = "mantis-testnet"
+address = "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU"
+decimals = 6
+denom = "transfer/channel-0/uusdc"
+
+[[tokens]]
+symbol = "DAI"
+chain = "ethereum-sepolia"
+address = "0x3e622317f8C93f7328350cF0B56d9eD4C620C5d6"
+decimals = 18
+"#;
+
+    const JSON: &str = r#"{
+        "tokens": [
+            {
+                "symbol": "USDC",
+                "chain": "mantis-testnet",
+                "address": "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU",
+                "decimals": 6,
+                "denom": "transfer/channel-0/uusdc"
+            },
+            {
+                "symbol": "DAI",
+                "chain": "ethereum-sepolia",
+                "address": "0x3e622317f8C93f7328350cF0B56d9eD4C620C5d6",
+                "decimals": 18
+            }
+        ]
+    }"#;
+
+    fn assert_loaded(registry: &TokenRegistry) {
+        let usdc = registry.get("USDC", MANTIS_TESTNET).unwrap();
+        assert_eq!(usdc.address, "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU");
+        assert_eq!(usdc.decimals, 6);
+        assert_eq!(usdc.cross_chain_denom(), "transfer/channel-0/uusdc");
+        let dai = registry.get("DAI", ETHEREUM_SEPOLIA).unwrap();
+        assert_eq!(dai.decimals, 18);
+        assert_eq!(dai.denom, None);
+        assert_eq!(dai.cross_chain_denom(), "0x3e622317f8c93f7328350cf0b56d9ed4c620c5d6");
+        assert_eq!(registry.tokens().count(), 2);
+    }
+
+    #[test]
+    fn get_ignores_symbol_case_but_not_chain() {
+        let registry = TokenRegistry::default();
+        let usdc = registry.get("usdc", ETHEREUM_MAINNET).unwrap();
+        assert_eq!(usdc.address, "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        assert_eq!(usdc.decimals, 6);
+        assert_eq!(
+            registry.get("USDC", SOLANA_MAINNET).unwrap().address,
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+        );
+        assert!(registry.get("USDC", MANTIS_TESTNET).is_none());
+        assert!(registry.get("UNKNOWN", ETHEREUM_MAINNET).is_none());
+    }
+
+    #[test]
+    fn resolves_readme_example_tokens() {
+        let registry = TokenRegistry::default();
+        assert_eq!(registry.get("SOL", SOLANA_MAINNET).unwrap().decimals, 9);
+        assert_eq!(registry.get("ETH", SOLANA_MAINNET).unwrap().decimals, 8);
+        assert_eq!(
+            registry.get("ETH", ETHEREUM_MAINNET).unwrap().address,
+            registry.get("WETH", ETHEREUM_MAINNET).unwrap().address
+        );
+    }
+
+    #[test]
+    fn builtin_tokens_are_valid_and_have_denoms() {
+        let registry = TokenRegistry::default();
+        TokenRegistry::from_tokens(registry.tokens().cloned().collect()).unwrap();
+        for token in registry.tokens() {
+            let denom = token.denom.as_deref().unwrap();
+            if token.chain.is_svm() {
+                assert_eq!(denom, token.address);
+            } else {
+                assert_eq!(denom, token.address.to_ascii_lowercase());
+            }
+        }
+    }
+
+    #[test]
+    fn loads_toml() {
+        assert_loaded(&TokenRegistry::from_toml_str(TOML).unwrap());
+    }
+
+    #[test]
+    fn loads_json() {
+        assert_loaded(&TokenRegistry::from_json_str(JSON).unwrap());
+    }
+
+    #[test]
+    fn loads_files_by_extension() {
+        let dir = std::env::temp_dir().join(format!("mantis-token-registry-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in [("tokens.toml", TOML), ("tokens.json", JSON)] {
+            let path = dir.join(name);
+            std::fs::write(&path, contents).unwrap();
+            assert_loaded(&TokenRegistry::from_file(&path).unwrap());
+        }
+        let path = dir.join("tokens.yaml");
+        std::fs::write(&path, TOML).unwrap();
+        assert!(matches!(
+            TokenRegistry::from_file(&path),
+            Err(TokenRegistryError::UnsupportedFormat(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_addresses_of_another_chain() {
+        let toml = r#"
+[[tokens]]
+symbol = "USDC"
+chain = "solana-mainnet"
+address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+decimals = 6
+"#;
+        assert!(matches!(
+            TokenRegistry::from_toml_str(toml),
+            Err(TokenRegistryError::InvalidAddress { chain: SOLANA_MAINNET, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_chains() {
+        let toml = r#"
+[[tokens]]
+symbol = "USDC"
+chain = "ethereum-goerli"
+address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+decimals = 6
+"#;
+        assert!(matches!(
+            TokenRegistry::from_toml_str(toml),
+            Err(TokenRegistryError::TomlError(_))
+        ));
+    }
+
+    #[test]
+    fn extend_overrides_same_symbol_and_chain() {
+        let mut registry = TokenRegistry::default();
+        let builtin = registry.tokens().count();
+        let overrides = r#"
+[[tokens]]
+symbol = "usdc"
+chain = "ethereum-sepolia"
+address = "0x94a9D9AC8a22534E3FaCa9F4e7F2E2cf85d5E4C8"
+decimals = 6
+
+[[tokens]]
+symbol = "USDC"
+chain = "mantis-testnet"
+address = "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU"
+decimals = 6
+"#;
+        registry.extend(TokenRegistry::from_toml_str(overrides).unwrap());
+
+        assert_eq!(registry.tokens().count(), builtin + 1);
+        assert_eq!(
+            registry.get("USDC", ETHEREUM_SEPOLIA).unwrap().address,
+            "0x94a9D9AC8a22534E3FaCa9F4e7F2E2cf85d5E4C8"
+        );
+        assert_eq!(
+            registry.get("USDC", ETHEREUM_MAINNET).unwrap().address,
+            "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+        );
+        assert!(registry.get("USDC", MANTIS_TESTNET).is_some());
+    }
+}
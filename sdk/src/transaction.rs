@@ -0,0 +1,54 @@
+use crate::events::EscrowEvent;
+
+/// Chain-agnostic view of a transaction, as returned by [`crate::Chain::get_transaction`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionSummary {
+    /// Transaction hash on Ethereum, transaction signature on Solana.
+    pub tx_hash: String,
+    pub status: TransactionStatus,
+    /// Fee paid in the chain's smallest native unit (wei, lamports), once included.
+    pub fee: Option<u128>,
+    /// Block number (slot on Solana), once included.
+    pub block_number: Option<u64>,
+    /// Escrow calls made by the transaction.
+    pub calls: Vec<EscrowCall>,
+    /// Escrow events emitted by the transaction.
+    pub events: Vec<EscrowEvent>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TransactionStatus {
+    Pending,
+    Success,
+    Failed,
+    /// Included, but the node didn't return its outcome.
+    Unknown,
+}
+
+/// Decoded escrow contract call (program instruction on Solana).
+///
+/// `intent_id` is `None` where the call doesn't carry it, e.g. on Ethereum the escrow
+/// assigns the id of new intents and on Solana settlement only passes the intent account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EscrowCall {
+    EscrowFunds {
+        intent_id: Option<String>,
+        user: String,
+        token_in: String,
+        amount_in: String,
+        token_out: String,
+        amount_out: String,
+    },
+    InitializeAuctioneer,
+    UpdateAuctionData {
+        intent_id: String,
+        amount_out: String,
+        winner_solver: String,
+    },
+    SendFundsToUser {
+        intent_id: Option<String>,
+    },
+    UserCancelIntent {
+        intent_id: Option<String>,
+    },
+}
@@ -0,0 +1,66 @@
+use alloy::network::TransactionBuilder;
+use alloy::node_bindings::{Anvil, AnvilInstance};
+use alloy::primitives::{Address, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::TransactionRequest;
+use alloy::signers::local::PrivateKeySigner;
+use mantis_sdk::ethereum::{ChainError, EthereumClient, Network};
+use std::sync::Arc;
+
+/// Returns a client of a fresh, funded signer, which the node can't sign for itself.
+async fn funded_client(anvil: &AnvilInstance, network: Network) -> (EthereumClient, Address) {
+    let signer = PrivateKeySigner::random();
+    let address = signer.address();
+
+    let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+    let funding = TransactionRequest::default()
+        .with_from(anvil.addresses()[0])
+        .with_to(address)
+        .with_value(U256::from(10u128.pow(18)));
+    provider.send_transaction(funding).await.unwrap().watch().await.unwrap();
+
+    let client = EthereumClient::new_with_url(
+        network,
+        Arc::new(signer),
+        anvil.endpoint_url(),
+        anvil.ws_endpoint_url(),
+    );
+    (client, address)
+}
+
+#[tokio::test]
+#[ignore = "requires anvil"]
+async fn signs_transactions_with_the_configured_signer() {
+    let anvil = Anvil::new().chain_id(Network::EthereumSepolia.chain_id()).spawn();
+    let (client, address) = funded_client(&anvil, Network::EthereumSepolia).await;
+    client.verify_chain_id().await.unwrap();
+
+    // approving on an address without code succeeds, so any token address does
+    let token = client.erc20(Address::random());
+    let first = token.approve(Address::random(), U256::from(1)).await.unwrap();
+    let second = token.approve(Address::random(), U256::from(2)).await.unwrap();
+
+    let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+    for (nonce, tx_hash) in [first, second].into_iter().enumerate() {
+        let tx = provider.get_transaction_by_hash(tx_hash).await.unwrap().unwrap();
+        assert_eq!(tx.from, address);
+        assert_eq!(tx.nonce, nonce as u64);
+        assert_eq!(tx.chain_id, Some(Network::EthereumSepolia.chain_id()));
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires anvil"]
+async fn rejects_node_of_another_chain() {
+    // anvil defaults to chain 31337
+    let anvil = Anvil::new().spawn();
+    let (client, _) = funded_client(&anvil, Network::EthereumSepolia).await;
+
+    match client.verify_chain_id().await {
+        Err(ChainError::ChainIdMismatch { expected, actual, .. }) => {
+            assert_eq!(expected, Network::EthereumSepolia.chain_id());
+            assert_eq!(actual, 31337);
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
@@ -0,0 +1,108 @@
+use mantis_sdk::quote::{
+    HttpQuoteProvider, Quote, QuoteError, QuoteFee, QuoteProvider, QuoteRequest, RouteHop,
+};
+use reqwest::{StatusCode, Url};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// Serves a single HTTP request with `status` and `body`, returning the request body it received.
+async fn mock_server(status: &'static str, body: &'static str) -> (Url, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/quote", listener.local_addr().unwrap()).parse().unwrap();
+
+    let handle = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        let request_body = loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            assert!(n > 0, "connection closed before the request was complete");
+            request.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                continue;
+            };
+            let content_length = head
+                .lines()
+                .find_map(|line| {
+                    let line = line.to_ascii_lowercase();
+                    line.strip_prefix("content-length:").map(|len| len.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            if body.len() >= content_length {
+                break body.to_string();
+            }
+        };
+
+        let response = format!(
+            "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        request_body
+    });
+
+    (url, handle)
+}
+
+fn request() -> QuoteRequest {
+    QuoteRequest {
+        token_in: "BTC".to_string(),
+        token_out: "ETH".to_string(),
+        amount_in: "1".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn http_provider_posts_request_and_decodes_quote() {
+    let (url, server) = mock_server(
+        "200 OK",
+        r#"{
+            "amount_out": "19500000000000000000",
+            "price": "19.5",
+            "fees": [{"kind": "solver", "token": "ETH", "amount": "50000000000000000"}],
+            "route": [{"venue": "ethereum", "token_in": "BTC", "token_out": "ETH"}]
+        }"#,
+    )
+    .await;
+
+    let quote = HttpQuoteProvider::new(url).get_quote(&request()).await.unwrap();
+
+    assert_eq!(
+        quote,
+        Quote {
+            amount_out: "19500000000000000000".to_string(),
+            price: "19.5".to_string(),
+            fees: vec![QuoteFee {
+                kind: "solver".to_string(),
+                token: "ETH".to_string(),
+                amount: "50000000000000000".to_string(),
+            }],
+            route: vec![RouteHop {
+                venue: "ethereum".to_string(),
+                token_in: "BTC".to_string(),
+                token_out: "ETH".to_string(),
+            }],
+        }
+    );
+    let sent: QuoteRequest = serde_json::from_str(&server.await.unwrap()).unwrap();
+    assert_eq!(sent, request());
+}
+
+#[tokio::test]
+async fn http_provider_reports_error_status() {
+    let (url, _server) = mock_server("404 Not Found", r#"{"error": "unknown token BTC"}"#).await;
+
+    let err = HttpQuoteProvider::new(url).get_quote(&request()).await.unwrap_err();
+
+    match err {
+        QuoteError::ResponseError { status, body } => {
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            assert!(body.contains("unknown token BTC"));
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+}